@@ -320,17 +320,28 @@ impl NodeConfig {
             return Ok(self);
         }
 
-        self.indexer.postgres_uri = env_or_default(
-            "INDEXER_DATABASE_URL",
-            self.indexer.postgres_uri,
-            must_be_set("postgres_uri", "INDEXER_DATABASE_URL"),
-        );
+        self.indexer.postgres_uri = match std::env::var(POSTGRES_URI_OVERRIDE_ENV_VAR) {
+            Ok(uri) => Some(uri),
+            Err(_) => env_or_default(
+                "INDEXER_DATABASE_URL",
+                self.indexer.postgres_uri,
+                must_be_set("postgres_uri", "INDEXER_DATABASE_URL"),
+            ),
+        };
+        // Not logging the value, it usually holds credentials
+        invariant(
+            self.indexer
+                .postgres_uri
+                .as_deref()
+                .map_or(false, is_valid_postgres_uri),
+            "Indexer postgres_uri is not a valid postgres connection string".into(),
+        )?;
 
         self.indexer.processor = env_or_default(
             "PROCESSOR_NAME",
             self.indexer
                 .processor
-                .or_else(|| Some("default_processor".to_string())),
+                .or_else(|| Some(DEFAULT_PROCESSOR.to_string())),
             None,
         );
 
@@ -350,6 +361,7 @@ impl NodeConfig {
             },
         };
 
+        self.indexer.dry_run = self.indexer.dry_run.or(Some(false));
         self.indexer.skip_migrations = self.indexer.skip_migrations.or(Some(false));
         self.indexer.check_chain_id = self.indexer.check_chain_id.or(Some(true));
         self.indexer.batch_size = default_if_zero(
@@ -367,9 +379,22 @@ impl NodeConfig {
         self.indexer.emit_every = self.indexer.emit_every.or(Some(0));
         self.indexer.gap_lookback_versions = env_or_default(
             "GAP_LOOKBACK_VERSIONS",
-            self.indexer.gap_lookback_versions.or(Some(1_500_000)),
+            self.indexer
+                .gap_lookback_versions
+                .or(Some(DEFAULT_GAP_LOOKBACK_VERSIONS)),
             None,
         );
+        self.indexer.db_pool_size = default_if_zero(
+            self.indexer.db_pool_size.map(|v| v as u64),
+            DEFAULT_DB_POOL_SIZE as u64,
+        )
+        .map(|v| v as u32);
+        self.indexer.db_connection_timeout_secs = default_if_zero(
+            self.indexer.db_connection_timeout_secs,
+            DEFAULT_DB_CONNECTION_TIMEOUT_SECS,
+        );
+        self.indexer.batch_timeout_secs =
+            default_if_zero(self.indexer.batch_timeout_secs, DEFAULT_BATCH_TIMEOUT_SECS);
 
         Ok(self)
     }
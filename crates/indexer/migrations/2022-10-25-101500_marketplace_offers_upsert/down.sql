@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE marketplace_offers DROP CONSTRAINT marketplace_offers_pkey;
+ALTER TABLE marketplace_offers
+ADD PRIMARY KEY (token_name, property_version, price, seller);
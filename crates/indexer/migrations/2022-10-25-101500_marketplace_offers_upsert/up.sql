@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- A token can only have one active listing, relisting it should overwrite the existing offer.
+-- Under the old key a relisted token could have several offers, so keep only the latest one per token first
+DELETE FROM marketplace_offers older USING marketplace_offers newer
+WHERE older.creator_address = newer.creator_address
+  AND older.collection_name = newer.collection_name
+  AND older.token_name = newer.token_name
+  AND older.property_version = newer.property_version
+  AND (older."timestamp", older.ctid) < (newer."timestamp", newer.ctid);
+ALTER TABLE marketplace_offers DROP CONSTRAINT marketplace_offers_pkey;
+ALTER TABLE marketplace_offers
+ADD PRIMARY KEY (
+    creator_address,
+    collection_name,
+    token_name,
+    property_version
+  );
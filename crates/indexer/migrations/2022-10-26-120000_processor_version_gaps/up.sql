@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Version ranges a processor skipped over, e.g. because it was started past its last processed version
+CREATE TABLE processor_version_gaps (
+  processor VARCHAR(50) NOT NULL,
+  gap_start_version BIGINT NOT NULL,
+  gap_end_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (processor, gap_start_version)
+);
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- Decoded token property maps, one row per property, so tokens can be filtered by trait
+CREATE TABLE token_properties (
+  -- sha256 of creator + collection_name + name
+  token_data_id_hash VARCHAR(64) NOT NULL,
+  property_version NUMERIC NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  property_key TEXT NOT NULL,
+  property_type TEXT NOT NULL,
+  -- decoded value for known types, otherwise the raw BCS hex
+  property_value TEXT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (
+    token_data_id_hash,
+    property_version,
+    transaction_version,
+    property_key
+  )
+);
+CREATE INDEX tp_key_value_index ON token_properties (property_key, property_value);
+CREATE INDEX tp_insat_index ON token_properties (inserted_at);
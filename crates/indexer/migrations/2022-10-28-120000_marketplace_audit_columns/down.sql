@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS marketplace_collections_audit ON marketplace_collections;
+DROP TRIGGER IF EXISTS marketplace_offers_audit ON marketplace_offers;
+DROP TRIGGER IF EXISTS marketplace_orders_audit ON marketplace_orders;
+DROP TRIGGER IF EXISTS marketplace_bids_audit ON marketplace_bids;
+DROP FUNCTION IF EXISTS marketplace_set_audit_columns;
+ALTER TABLE marketplace_collections DROP COLUMN IF EXISTS created_at,
+  DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE marketplace_offers DROP COLUMN IF EXISTS created_at,
+  DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE marketplace_orders DROP COLUMN IF EXISTS created_at,
+  DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE marketplace_bids DROP COLUMN IF EXISTS created_at,
+  DROP COLUMN IF EXISTS updated_at;
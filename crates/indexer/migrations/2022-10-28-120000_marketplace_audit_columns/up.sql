@@ -0,0 +1,38 @@
+-- Your SQL goes here
+-- Track when the indexer first wrote and last touched each marketplace row, independent of the
+-- transaction timestamp, so clients can tell how fresh the index is
+ALTER TABLE marketplace_collections
+ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT NOW();
+ALTER TABLE marketplace_offers
+ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT NOW();
+ALTER TABLE marketplace_orders
+ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT NOW();
+ALTER TABLE marketplace_bids
+ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT NOW();
+-- The database owns both columns and the indexer never writes them, so a trigger moves updated_at on
+-- every write. It uses clock_timestamp() rather than NOW(), which is the start of the surrounding
+-- transaction: a batch writes inside one transaction, so two writes to a row in it would otherwise get
+-- the same updated_at.
+CREATE OR REPLACE FUNCTION marketplace_set_audit_columns() RETURNS TRIGGER AS $$
+BEGIN
+  IF TG_OP = 'INSERT' THEN
+    NEW.created_at = clock_timestamp();
+  ELSE
+    NEW.created_at = OLD.created_at;
+  END IF;
+  NEW.updated_at = clock_timestamp();
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+CREATE TRIGGER marketplace_collections_audit BEFORE INSERT OR UPDATE ON marketplace_collections
+FOR EACH ROW EXECUTE FUNCTION marketplace_set_audit_columns();
+CREATE TRIGGER marketplace_offers_audit BEFORE INSERT OR UPDATE ON marketplace_offers
+FOR EACH ROW EXECUTE FUNCTION marketplace_set_audit_columns();
+CREATE TRIGGER marketplace_orders_audit BEFORE INSERT OR UPDATE ON marketplace_orders
+FOR EACH ROW EXECUTE FUNCTION marketplace_set_audit_columns();
+CREATE TRIGGER marketplace_bids_audit BEFORE INSERT OR UPDATE ON marketplace_bids
+FOR EACH ROW EXECUTE FUNCTION marketplace_set_audit_columns();
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS curr_to_oa_ltv_index;
+DROP INDEX IF EXISTS curr_to_cdih_ltv_index;
+DROP INDEX IF EXISTS curr_to_crea_cn_ltv_index;
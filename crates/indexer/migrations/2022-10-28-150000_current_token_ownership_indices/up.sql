@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- indices backing the CurrentTokenOwnership lookups, newest ownership first
+CREATE INDEX IF NOT EXISTS curr_to_oa_ltv_index ON current_token_ownerships (
+  owner_address,
+  last_transaction_version DESC
+);
+CREATE INDEX IF NOT EXISTS curr_to_cdih_ltv_index ON current_token_ownerships (
+  collection_data_id_hash,
+  last_transaction_version DESC
+);
+CREATE INDEX IF NOT EXISTS curr_to_crea_cn_ltv_index ON current_token_ownerships (
+  creator_address,
+  collection_name,
+  last_transaction_version DESC
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE marketplace_collections DROP COLUMN IF EXISTS txn_version;
+ALTER TABLE marketplace_offers DROP COLUMN IF EXISTS txn_version;
+ALTER TABLE marketplace_orders DROP COLUMN IF EXISTS txn_version;
+ALTER TABLE marketplace_bids DROP COLUMN IF EXISTS txn_version;
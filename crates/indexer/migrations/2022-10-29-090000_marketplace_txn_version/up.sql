@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Version of the transaction that last wrote each marketplace row. Rows indexed before this
+-- migration don't know theirs, so they're backfilled with 0
+ALTER TABLE marketplace_collections
+ADD COLUMN txn_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE marketplace_offers
+ADD COLUMN txn_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE marketplace_orders
+ADD COLUMN txn_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE marketplace_bids
+ADD COLUMN txn_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE marketplace_collections
+ALTER COLUMN txn_version DROP DEFAULT;
+ALTER TABLE marketplace_offers
+ALTER COLUMN txn_version DROP DEFAULT;
+ALTER TABLE marketplace_orders
+ALTER COLUMN txn_version DROP DEFAULT;
+ALTER TABLE marketplace_bids
+ALTER COLUMN txn_version DROP DEFAULT;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- Decoded default property maps of token datas, one row per property. Tokens that never had their
+-- properties mutated only have these, so they're what to filter on for traits shared by a whole token data
+CREATE TABLE token_data_properties (
+  -- sha256 of creator + collection_name + name
+  token_data_id_hash VARCHAR(64) NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  property_key TEXT NOT NULL,
+  property_type TEXT NOT NULL,
+  -- decoded value for known types, otherwise the raw BCS hex
+  property_value TEXT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (
+    token_data_id_hash,
+    transaction_version,
+    property_key
+  )
+);
+CREATE INDEX tdp_key_value_index ON token_data_properties (property_key, property_value);
+CREATE INDEX tdp_insat_index ON token_data_properties (inserted_at);
@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE tokens DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE token_ownerships DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE current_token_ownerships DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE token_datas DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE current_token_datas DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE collection_datas DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE current_collection_datas DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE token_activities DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE current_token_pending_claims DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE marketplace_collections DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE marketplace_offers DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE marketplace_orders DROP COLUMN IF EXISTS names_encoded;
+ALTER TABLE marketplace_bids DROP COLUMN IF EXISTS names_encoded;
@@ -0,0 +1,29 @@
+-- Your SQL goes here
+-- Whether the name columns of a row hold escaped names (see `sanitize_name`) rather than the on-chain ones.
+-- Rows indexed before this migration were never escaped, so they're backfilled with false
+ALTER TABLE tokens
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE token_ownerships
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE current_token_ownerships
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE token_datas
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE current_token_datas
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE collection_datas
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE current_collection_datas
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE token_activities
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE current_token_pending_claims
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE marketplace_collections
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE marketplace_offers
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE marketplace_orders
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE marketplace_bids
+ADD COLUMN names_encoded BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE processor_version_gaps DROP COLUMN IF EXISTS reason;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Why a range was recorded, so e.g. transactions processed without their write set can be told apart from
+-- versions that were never processed. Earlier rows don't say, so they're backfilled with 'unknown'
+ALTER TABLE processor_version_gaps
+ADD COLUMN reason VARCHAR(50) NOT NULL DEFAULT 'unknown';
+ALTER TABLE processor_version_gaps
+ALTER COLUMN reason DROP DEFAULT;
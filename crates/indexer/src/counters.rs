@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    register_int_counter, register_int_counter_vec, register_int_gauge_vec, IntCounter,
-    IntCounterVec, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -37,6 +37,36 @@ pub static PROCESSOR_SUCCESSES: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Time a processor spends on a batch, from the batch being dequeued until processing returns
+pub static PROCESSOR_BATCH_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "indexer_processor_batch_latency_secs",
+        "Time a processor spends on a batch, from the batch being dequeued until processing returns",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of times a processor task has timed out on a batch and retried it
+pub static PROCESSOR_BATCH_TIMEOUTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_batch_timeout_count",
+        "Number of times a processor task has timed out on a batch and retried it",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of times a processor task has retried a batch after it failed with a database error
+pub static PROCESSOR_DATABASE_ERROR_RETRIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_database_error_retry_count",
+        "Number of times a processor task has retried a batch after it failed with a database error",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
 /// Number of times the connection pool has timed out when trying to get a connection
 pub static UNABLE_TO_GET_CONNECTION: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -55,6 +85,33 @@ pub static GOT_CONNECTION: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of connections currently held by the connection pool, both idle and in use
+pub static DB_POOL_CONNECTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_db_pool_connections",
+        "Number of connections currently held by the connection pool"
+    )
+    .unwrap()
+});
+
+/// Number of idle connections in the connection pool. Sitting at zero means the pool is saturated.
+pub static DB_POOL_IDLE_CONNECTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_db_pool_idle_connections",
+        "Number of idle connections in the connection pool"
+    )
+    .unwrap()
+});
+
+/// Number of times a connection checkout from the pool has timed out
+pub static DB_POOL_CHECKOUT_TIMEOUTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "indexer_db_pool_checkout_timeouts",
+        "Number of times a connection checkout from the pool has timed out"
+    )
+    .unwrap()
+});
+
 /// Number of times the indexer has been unable to fetch a transaction. Ideally zero.
 pub static UNABLE_TO_FETCH_TRANSACTION: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -64,6 +121,16 @@ pub static UNABLE_TO_FETCH_TRANSACTION: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of times the fetcher retried a failed read from storage, by what it was reading
+pub static FETCHER_RETRIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_fetcher_retry_count",
+        "Number of times the fetcher retried a failed read from storage",
+        &["operation"]
+    )
+    .unwrap()
+});
+
 /// Number of times the indexer has been able to fetch a transaction
 pub static FETCHED_TRANSACTION: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -73,6 +140,47 @@ pub static FETCHED_TRANSACTION: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of version gaps a processor has skipped over. Ideally zero.
+pub static PROCESSOR_VERSION_GAPS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_version_gap_count",
+        "Number of version gaps a processor has skipped over",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of times a processor's start version was moved up because the node had already pruned it
+pub static PROCESSOR_START_VERSION_CLAMPED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_start_version_clamped_count",
+        "Number of times a processor's start version was moved up past pruned versions",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of rows processors have produced, per table. A table sitting at zero over a long range
+/// usually means its models have stopped parsing.
+pub static PROCESSOR_ROWS_PRODUCED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_rows_produced_count",
+        "Number of rows processors have produced, per table",
+        &["processor_name", "table_name"]
+    )
+    .unwrap()
+});
+
+/// Seconds between now and the newest transaction timestamp a processor has handled, i.e. how stale its data is
+pub static PROCESSOR_DATA_FRESHNESS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_data_freshness_secs",
+        "Seconds since the timestamp of the newest transaction a processor has handled",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
 /// Max version processed
 pub static LATEST_PROCESSED_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -82,3 +190,33 @@ pub static LATEST_PROCESSED_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Number of on-chain names that had to be escaped before being stored
+pub static SANITIZED_NAMES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_sanitized_name_count",
+        "Number of on-chain names that had to be escaped before being stored",
+        &["model"]
+    )
+    .unwrap()
+});
+
+/// Number of user transactions whose write set was dropped for exceeding `max_writeset_changes_per_txn`
+pub static PROCESSOR_OVERSIZED_TRANSACTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_oversized_transaction_count",
+        "Number of user transactions whose write set was dropped for exceeding max_writeset_changes_per_txn",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of rows a processor skipped because postgres rejected their data, per table
+pub static PROCESSOR_SKIPPED_ROWS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_skipped_row_count",
+        "Number of rows a processor skipped because postgres rejected their data",
+        &["processor_name", "table_name"]
+    )
+    .unwrap()
+});
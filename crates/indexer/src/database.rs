@@ -3,14 +3,21 @@
 
 //! Database-related functions
 #![allow(clippy::extra_unused_lifetimes)]
-use crate::util::remove_null_bytes;
+use crate::{
+    counters::{DB_POOL_CHECKOUT_TIMEOUTS, DB_POOL_CONNECTIONS, DB_POOL_IDLE_CONNECTIONS},
+    util::remove_null_bytes,
+};
 use diesel::{
     pg::{Pg, PgConnection},
     query_builder::{AstPass, Query, QueryFragment},
-    r2d2::{ConnectionManager, PoolError, PooledConnection},
+    r2d2::{
+        event::TimeoutEvent, ConnectionManager, CustomizeConnection, HandleEvent, PoolError,
+        PooledConnection,
+    },
+    result::{DatabaseErrorKind, Error},
     QueryResult, RunQueryDsl,
 };
-use std::{cmp::min, sync::Arc};
+use std::{cmp::min, fmt::Debug, sync::Arc, time::Duration};
 
 pub type PgPool = diesel::r2d2::Pool<ConnectionManager<PgConnection>>;
 pub type PgDbPool = Arc<PgPool>;
@@ -24,13 +31,26 @@ pub struct UpsertFilterLatestTransactionQuery<T> {
     where_clause: Option<&'static str>,
 }
 
-pub const MAX_DIESEL_PARAM_SIZE: u16 = u16::MAX;
+/// How often `report_pool_metrics` samples the state of the connection pool
+pub const POOL_METRICS_INTERVAL: Duration = Duration::from_secs(5);
+
+pub const MAX_DIESEL_PARAM_SIZE: usize = u16::MAX as usize;
+
+/// How many times a transaction is attempted when it fails with a transient error (see `is_transient_error`)
+pub const MAX_QUERY_ATTEMPTS: u32 = 3;
+/// Delay before the first retry of a transient error. Doubles after every failed attempt.
+pub const QUERY_RETRY_BASE_DELAY_MILLIS: u64 = 50;
 
 /// Given diesel has a limit of how many parameters can be inserted in a single operation (u16::MAX)
 /// we may need to chunk an array of items based on how many columns are in the table.
+/// `max_params` is normally `MAX_DIESEL_PARAM_SIZE` but can be lowered for models that need smaller chunks.
 /// This function returns boundaries of chunks in the form of (start_index, end_index)
-pub fn get_chunks(num_items_to_insert: usize, column_count: usize) -> Vec<(usize, usize)> {
-    let max_item_size = MAX_DIESEL_PARAM_SIZE as usize / column_count;
+pub fn get_chunks(
+    num_items_to_insert: usize,
+    column_count: usize,
+    max_params: usize,
+) -> Vec<(usize, usize)> {
+    let max_item_size = max_params / column_count;
     let mut chunk: (usize, usize) = (0, min(num_items_to_insert, max_item_size));
     let mut chunks = vec![chunk];
     while chunk.1 != num_items_to_insert {
@@ -43,22 +63,136 @@ pub fn get_chunks(num_items_to_insert: usize, column_count: usize) -> Vec<(usize
     chunks
 }
 
+/// How a row is identified in logs, e.g. when `clean_data_for_db` had to change it
+pub trait LoggableRow {
+    /// Version of the transaction that wrote the row
+    fn txn_version(&self) -> i64;
+    /// The row's primary key, formatted for logs
+    fn pk(&self) -> String;
+}
+
+/// A row that `clean_data_for_db` had to change
+#[derive(Debug, PartialEq, Eq)]
+pub struct CleanedRow {
+    pub txn_version: i64,
+    pub pk: String,
+}
+
 /// This function will clean the data for postgres. Currently it has support for removing
 /// null bytes from strings but in the future we will add more functionality.
-pub fn clean_data_for_db<T: serde::Serialize + for<'de> serde::Deserialize<'de>>(
+/// Also returns the rows it had to change, to be passed to `log_cleaned_rows`.
+pub fn clean_data_for_db<T: serde::Serialize + for<'de> serde::Deserialize<'de> + LoggableRow>(
     items: Vec<T>,
     should_remove_null_bytes: bool,
-) -> Vec<T> {
-    if should_remove_null_bytes {
-        items.iter().map(remove_null_bytes).collect()
-    } else {
-        items
+) -> (Vec<T>, Vec<CleanedRow>) {
+    if !should_remove_null_bytes {
+        return (items, vec![]);
+    }
+    let mut modified = vec![];
+    let cleaned = items
+        .into_iter()
+        .map(|item| match remove_null_bytes(&item) {
+            Some(cleaned) => {
+                modified.push(CleanedRow {
+                    txn_version: cleaned.txn_version(),
+                    pk: cleaned.pk(),
+                });
+                cleaned
+            }
+            None => item,
+        })
+        .collect();
+    (cleaned, modified)
+}
+
+/// Logs the rows `clean_data_for_db` changed, per table, so a retry that only went through because
+/// the data was altered doesn't go unnoticed.
+pub fn log_cleaned_rows(
+    name: &'static str,
+    start_version: u64,
+    end_version: u64,
+    cleaned: &[(&'static str, &Vec<CleanedRow>)],
+) {
+    for (table_name, rows) in cleaned {
+        for row in rows.iter() {
+            aptos_logger::warn!(
+                name = name,
+                start_version = start_version,
+                end_version = end_version,
+                table_name = table_name,
+                txn_version = row.txn_version,
+                pk = row.pk,
+                "Cleaned row before retrying insert",
+            );
+        }
+    }
+}
+
+/// Logs the rows a processor would have inserted into `table_name`. Used instead of inserting in dry run mode.
+pub fn log_dry_run<T: Debug>(name: &'static str, table_name: &'static str, rows: &[T]) {
+    for row in rows {
+        aptos_logger::warn!(
+            name = name,
+            table_name = table_name,
+            row = ?row,
+            "Dry run, skipping insert",
+        );
     }
 }
 
-pub fn new_db_pool(database_url: &str) -> Result<PgDbPool, PoolError> {
+/// If `statement_timeout` is set, postgres cancels any statement on the pool's connections that runs longer
+/// than it, so a stuck query errors out instead of blocking its caller forever.
+pub fn new_db_pool(
+    database_url: &str,
+    pool_size: u32,
+    min_idle: Option<u32>,
+    connection_timeout_secs: u64,
+    statement_timeout: Option<Duration>,
+) -> Result<PgDbPool, PoolError> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
-    PgPool::builder().build(manager).map(Arc::new)
+    let mut builder = PgPool::builder()
+        .max_size(pool_size)
+        .min_idle(min_idle)
+        .connection_timeout(Duration::from_secs(connection_timeout_secs))
+        .event_handler(Box::new(PoolEventHandler));
+    if let Some(statement_timeout) = statement_timeout {
+        builder = builder.connection_customizer(Box::new(StatementTimeout(statement_timeout)));
+    }
+    builder.build(manager).map(Arc::new)
+}
+
+/// Sets `statement_timeout` on every connection as the pool opens it
+#[derive(Debug)]
+struct StatementTimeout(Duration);
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for StatementTimeout {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        diesel::sql_query(format!("SET statement_timeout = {}", self.0.as_millis()))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Counts checkout timeouts for every caller of the pool, not just `TransactionProcessor::get_conn`
+#[derive(Debug)]
+struct PoolEventHandler;
+
+impl HandleEvent for PoolEventHandler {
+    fn handle_timeout(&self, _event: TimeoutEvent) {
+        DB_POOL_CHECKOUT_TIMEOUTS.inc();
+    }
+}
+
+/// Samples the size of the connection pool into the pool gauges every `POOL_METRICS_INTERVAL`.
+/// Meant to be spawned as a background task; it never returns.
+pub async fn report_pool_metrics(pool: PgDbPool) {
+    loop {
+        let state = pool.state();
+        DB_POOL_CONNECTIONS.set(state.connections as i64);
+        DB_POOL_IDLE_CONNECTIONS.set(state.idle_connections as i64);
+        tokio::time::sleep(POOL_METRICS_INTERVAL).await;
+    }
 }
 
 pub fn execute_with_better_error<
@@ -93,6 +227,45 @@ where
     res
 }
 
+/// Runs `transaction`, normally a whole `build_transaction().run(...)`, again with backoff while it fails with a
+/// transient error. This has to wrap the transaction rather than a single statement: postgres aborts the whole
+/// transaction on a serialization failure or deadlock, so only a fresh transaction (and snapshot) can succeed.
+pub async fn retry_transient_errors<T>(
+    name: &'static str,
+    mut transaction: impl FnMut() -> QueryResult<T>,
+) -> QueryResult<T> {
+    let mut attempt = 1;
+    loop {
+        match transaction() {
+            Err(ref e) if is_transient_error(e) && attempt < MAX_QUERY_ATTEMPTS => {
+                let delay = QUERY_RETRY_BASE_DELAY_MILLIS * 2u64.pow(attempt - 1);
+                aptos_logger::warn!(
+                    name = name,
+                    attempt = attempt,
+                    delay_millis = delay,
+                    "Transient error running transaction, will retry: {:?}",
+                    e
+                );
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Serialization failures (40001) and deadlocks (40P01) are caused by concurrent writers and are safe to retry.
+/// Diesel has no dedicated error kind for deadlocks so we have to look at the message instead.
+fn is_transient_error(error: &Error) -> bool {
+    match error {
+        Error::DatabaseError(DatabaseErrorKind::SerializationFailure, _) => true,
+        Error::DatabaseError(DatabaseErrorKind::Unknown, info) => {
+            info.message().contains("deadlock detected")
+        }
+        _ => false,
+    }
+}
+
 /// Section below is required to modify the query.
 impl<T: Query> Query for UpsertFilterLatestTransactionQuery<T> {
     type SqlType = T::SqlType;
@@ -116,23 +289,169 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use diesel::{sql_query, Connection};
 
     #[tokio::test]
     async fn test_get_chunks_logic() {
-        assert_eq!(get_chunks(10, 5), vec![(0, 10)]);
-        assert_eq!(get_chunks(65535, 1), vec![(0, 65535)]);
+        assert_eq!(get_chunks(10, 5, MAX_DIESEL_PARAM_SIZE), vec![(0, 10)]);
+        assert_eq!(
+            get_chunks(65535, 1, MAX_DIESEL_PARAM_SIZE),
+            vec![(0, 65535)]
+        );
         // 200,000 total items will take 6 buckets. Each bucket can only be 3276 size.
         assert_eq!(
-            get_chunks(10000, 20),
+            get_chunks(10000, 20, MAX_DIESEL_PARAM_SIZE),
             vec![(0, 3276), (3276, 6552), (6552, 9828), (9828, 10000)]
         );
         assert_eq!(
-            get_chunks(65535, 2),
+            get_chunks(65535, 2, MAX_DIESEL_PARAM_SIZE),
             vec![(0, 32767), (32767, 65534), (65534, 65535)]
         );
         assert_eq!(
-            get_chunks(65535, 3),
+            get_chunks(65535, 3, MAX_DIESEL_PARAM_SIZE),
             vec![(0, 21845), (21845, 43690), (43690, 65535)]
         );
+        assert_eq!(get_chunks(10, 5, 25), vec![(0, 5), (5, 10)]);
+    }
+
+    #[test]
+    fn test_clean_data_for_db_reports_modified_rows() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Row {
+            version: i64,
+            name: String,
+        }
+        impl LoggableRow for Row {
+            fn txn_version(&self) -> i64 {
+                self.version
+            }
+
+            fn pk(&self) -> String {
+                self.version.to_string()
+            }
+        }
+        let rows = vec![
+            Row {
+                version: 1,
+                name: "clean".to_string(),
+            },
+            Row {
+                version: 2,
+                name: "null\u{0000}byte".to_string(),
+            },
+        ];
+
+        let (cleaned, modified) = clean_data_for_db(rows, true);
+        assert_eq!(
+            modified,
+            vec![CleanedRow {
+                txn_version: 2,
+                pk: "2".to_string(),
+            }]
+        );
+        assert_eq!(cleaned[0].name, "clean");
+        assert!(!cleaned[1].name.contains('\u{0000}'));
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        let db_error =
+            |kind, message: &str| Error::DatabaseError(kind, Box::new(message.to_string()));
+
+        assert!(is_transient_error(&db_error(
+            DatabaseErrorKind::SerializationFailure,
+            "could not serialize access due to concurrent update"
+        )));
+        assert!(is_transient_error(&db_error(
+            DatabaseErrorKind::Unknown,
+            "deadlock detected"
+        )));
+        assert!(!is_transient_error(&db_error(
+            DatabaseErrorKind::UniqueViolation,
+            "duplicate key value violates unique constraint"
+        )));
+        assert!(!is_transient_error(&db_error(
+            DatabaseErrorKind::Unknown,
+            "value too long for type character varying(100)"
+        )));
+        assert!(!is_transient_error(&Error::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let _db_lock = crate::lock_test_database();
+        let database_url = std::env::var("INDEXER_DATABASE_URL").unwrap();
+        let mut conn = PgConnection::establish(&database_url).unwrap();
+        let mut other_conn = PgConnection::establish(&database_url).unwrap();
+        sql_query("DROP TABLE IF EXISTS transient_retry_test")
+            .execute(&mut conn)
+            .unwrap();
+        sql_query("CREATE TABLE transient_retry_test (id INT PRIMARY KEY, value INT NOT NULL)")
+            .execute(&mut conn)
+            .unwrap();
+        sql_query("INSERT INTO transient_retry_test VALUES (1, 0)")
+            .execute(&mut conn)
+            .unwrap();
+
+        let mut attempts = 0;
+        let result = retry_transient_errors("test", || {
+            attempts += 1;
+            let first_attempt = attempts == 1;
+            conn.build_transaction()
+                .repeatable_read()
+                .run::<_, Error, _>(|conn| {
+                    // Takes the transaction's snapshot
+                    sql_query("SELECT * FROM transient_retry_test").execute(conn)?;
+                    if first_attempt {
+                        // Committed after our snapshot, so our own update fails with a serialization failure
+                        sql_query("UPDATE transient_retry_test SET value = value + 1")
+                            .execute(&mut other_conn)?;
+                    }
+                    sql_query("UPDATE transient_retry_test SET value = value + 10").execute(conn)
+                })
+        })
+        .await;
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(attempts, 2);
+
+        // Anything else fails on the first attempt
+        let mut attempts = 0;
+        let result = retry_transient_errors("test", || {
+            attempts += 1;
+            sql_query("INSERT INTO transient_retry_test VALUES (1, 0)").execute(&mut conn)
+        })
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _))
+        ));
+        assert_eq!(attempts, 1);
+
+        sql_query("DROP TABLE transient_retry_test")
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_get_chunks_within_param_limit() {
+        use crate::models::marketplace_models::{
+            bids::MarketplaceBids, collections::MarketplaceCollection, offers::MarketplaceOffer,
+            orders::MarketplaceOrder,
+        };
+        use field_count::FieldCount;
+
+        for column_count in [
+            MarketplaceCollection::field_count(),
+            MarketplaceOffer::field_count(),
+            MarketplaceOrder::field_count(),
+            MarketplaceBids::field_count(),
+        ] {
+            for (start, end) in get_chunks(100_000, column_count, MAX_DIESEL_PARAM_SIZE) {
+                assert!((end - start) * column_count <= MAX_DIESEL_PARAM_SIZE);
+            }
+        }
     }
 }
@@ -13,13 +13,50 @@ pub enum TransactionProcessingError {
     ConnectionPoolError(ErrorWithVersionAndName),
     /// Could not commit the transaction
     TransactionCommitError(ErrorWithVersionAndName),
+    /// Could not parse a transaction. Retrying would fail the same way, so the indexer leaves these
+    /// versions marked as failed, records them as a gap and processes the rest of the batch without them
+    ParseError(ErrorWithVersionAndName),
+    /// A database query failed while processing the batch. These are worth retrying
+    DatabaseError(ErrorWithVersionAndName),
 }
 
 impl TransactionProcessingError {
+    /// Wraps an error raised while turning the transaction at `txn_version` into rows. Anything that came
+    /// out of a diesel query is a `DatabaseError` for the whole batch; otherwise only that transaction
+    /// couldn't be parsed.
+    pub fn from_processing_error(
+        error: Error,
+        txn_version: u64,
+        start_version: u64,
+        end_version: u64,
+        name: &'static str,
+    ) -> Self {
+        if error
+            .chain()
+            .any(|cause| cause.is::<diesel::result::Error>())
+        {
+            TransactionProcessingError::DatabaseError((error, start_version, end_version, name))
+        } else {
+            TransactionProcessingError::ParseError((error, txn_version, txn_version, name))
+        }
+    }
+
     pub fn inner(&self) -> &ErrorWithVersionAndName {
         match self {
             TransactionProcessingError::ConnectionPoolError(ewv) => ewv,
             TransactionProcessingError::TransactionCommitError(ewv) => ewv,
+            TransactionProcessingError::ParseError(ewv) => ewv,
+            TransactionProcessingError::DatabaseError(ewv) => ewv,
+        }
+    }
+
+    /// Short name of the variant, used as a log field so alerts can tell the kinds of failure apart
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            TransactionProcessingError::ConnectionPoolError(_) => "connection_pool",
+            TransactionProcessingError::TransactionCommitError(_) => "transaction_commit",
+            TransactionProcessingError::ParseError(_) => "parse",
+            TransactionProcessingError::DatabaseError(_) => "database",
         }
     }
 }
@@ -1,13 +1,15 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION};
+use crate::counters::{FETCHED_TRANSACTION, FETCHER_RETRIES, UNABLE_TO_FETCH_TRANSACTION};
+use anyhow::ensure;
 use aptos_api::Context;
 use aptos_api_types::{AsConverter, LedgerInfo, Transaction, TransactionOnChainData};
 use aptos_logger::prelude::*;
 use aptos_vm::data_cache::StorageAdapterOwned;
 use futures::channel::mpsc;
 use futures::{SinkExt, StreamExt};
+use rand::Rng;
 use std::sync::Arc;
 use std::time::Duration;
 use storage_interface::state_view::DbStateView;
@@ -18,6 +20,7 @@ const RETRY_TIME_MILLIS: u64 = 300;
 const MAX_RETRY_TIME_MILLIS: u64 = 120000;
 const TRANSACTION_FETCH_BATCH_SIZE: u16 = 500;
 const TRANSACTION_CHANNEL_SIZE: usize = 35;
+const FETCH_TASKS: usize = 5;
 
 #[derive(Debug)]
 pub struct Fetcher {
@@ -57,18 +60,30 @@ impl Fetcher {
     /// If there are, it will set the highest known version
     async fn ensure_highest_known_version(&mut self) {
         let mut empty_loops = 0;
+        let mut failed_attempts = 0;
         while self.highest_known_version == 0 || self.current_version > self.highest_known_version {
-            if empty_loops > 0 {
+            if failed_attempts > 0 {
+                tokio::time::sleep(backoff_with_jitter(
+                    failed_attempts - 1,
+                    self.options.starting_retry_time,
+                    self.options.max_retry_time,
+                ))
+                .await;
+            } else if empty_loops > 0 {
                 tokio::time::sleep(self.options.starting_retry_time).await;
             }
             empty_loops += 1;
             if let Err(err) = self.set_highest_known_version() {
+                FETCHER_RETRIES.with_label_values(&["ledger_info"]).inc();
+                failed_attempts += 1;
                 error!(
                     error = format!("{:?}", err),
+                    failed_attempts = failed_attempts,
                     "Failed to set highest known version"
                 );
                 continue;
             } else {
+                failed_attempts = 0;
                 sample!(
                     SampleRate::Frequency(10),
                     aptos_logger::info!(
@@ -114,9 +129,11 @@ impl Fetcher {
 
                 let context = self.context.clone();
                 let highest_known_version = self.highest_known_version;
+                let options = self.options.clone();
                 let task = tokio::spawn(async move {
                     fetch_nexts(
                         context,
+                        &options,
                         starting_version,
                         highest_known_version,
                         num_transactions_to_fetch,
@@ -173,8 +190,19 @@ impl Fetcher {
     }
 }
 
+/// Exponential backoff starting at `base` and capped at `cap`, with up to half of it randomized
+/// so that fetch tasks failing together don't all retry at the same moment
+pub(crate) fn backoff_with_jitter(attempt: u32, base: Duration, cap: Duration) -> Duration {
+    let backoff = base
+        .checked_mul(2u32.saturating_pow(attempt))
+        .map_or(cap, |backoff| backoff.min(cap));
+    let half_millis = backoff.as_millis() as u64 / 2;
+    Duration::from_millis(half_millis + rand::thread_rng().gen_range(0, half_millis + 1))
+}
+
 async fn fetch_raw_txns_with_retries(
     context: Arc<Context>,
+    options: &TransactionFetcherOptions,
     starting_version: u64,
     ledger_version: u64,
     num_transactions_to_fetch: u16,
@@ -203,6 +231,7 @@ async fn fetch_raw_txns_with_retries(
                         num_transactions_to_fetch, retries, starting_version, err
                     );
                 } else {
+                    FETCHER_RETRIES.with_label_values(&["transactions"]).inc();
                     error!(
                         starting_version = starting_version,
                         num_transactions = num_transactions_to_fetch,
@@ -210,7 +239,12 @@ async fn fetch_raw_txns_with_retries(
                         "Could not fetch transactions: will retry",
                     );
                 }
-                tokio::time::sleep(Duration::from_millis(300)).await;
+                tokio::time::sleep(backoff_with_jitter(
+                    retries as u32 - 1,
+                    options.starting_retry_time,
+                    options.max_retry_time,
+                ))
+                .await;
             }
         }
     }
@@ -218,6 +252,7 @@ async fn fetch_raw_txns_with_retries(
 
 async fn fetch_nexts(
     context: Arc<Context>,
+    options: &TransactionFetcherOptions,
     starting_version: u64,
     ledger_version: u64,
     num_transactions_to_fetch: u16,
@@ -226,6 +261,7 @@ async fn fetch_nexts(
 
     let raw_txns = fetch_raw_txns_with_retries(
         context.clone(),
+        options,
         starting_version,
         ledger_version,
         num_transactions_to_fetch,
@@ -390,9 +426,94 @@ impl TransactionFetcherOptions {
     }
 }
 
+impl TransactionFetcherOptions {
+    pub fn builder() -> TransactionFetcherOptionsBuilder {
+        TransactionFetcherOptionsBuilder::default()
+    }
+}
+
 impl Default for TransactionFetcherOptions {
     fn default() -> Self {
-        TransactionFetcherOptions::new(None, None, None, None, 5)
+        TransactionFetcherOptions::new(None, None, None, None, FETCH_TASKS)
+    }
+}
+
+/// Named alternative to `TransactionFetcherOptions::new`. Anything left unset gets its default,
+/// and `build` rejects sizes that were explicitly set to 0.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionFetcherOptionsBuilder {
+    starting_retry_time_millis: Option<u64>,
+    max_retry_time_millis: Option<u64>,
+    batch_size: Option<u16>,
+    max_pending_batches: Option<usize>,
+    fetch_tasks: Option<usize>,
+}
+
+impl TransactionFetcherOptionsBuilder {
+    pub fn starting_retry_time_millis(mut self, starting_retry_time_millis: u64) -> Self {
+        self.starting_retry_time_millis = Some(starting_retry_time_millis);
+        self
+    }
+
+    pub fn max_retry_time_millis(mut self, max_retry_time_millis: u64) -> Self {
+        self.max_retry_time_millis = Some(max_retry_time_millis);
+        self
+    }
+
+    /// Number of transactions fetched per batch
+    pub fn batch_size(mut self, batch_size: u16) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Number of fetched batches that can wait in the channel before fetching pauses
+    pub fn max_pending_batches(mut self, max_pending_batches: usize) -> Self {
+        self.max_pending_batches = Some(max_pending_batches);
+        self
+    }
+
+    /// Number of concurrent fetch tasks
+    pub fn fetch_tasks(mut self, fetch_tasks: usize) -> Self {
+        self.fetch_tasks = Some(fetch_tasks);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<TransactionFetcherOptions> {
+        ensure!(
+            self.starting_retry_time_millis != Some(0),
+            "starting_retry_time_millis must be greater than 0"
+        );
+        ensure!(
+            self.max_retry_time_millis != Some(0),
+            "max_retry_time_millis must be greater than 0"
+        );
+        ensure!(
+            self.batch_size != Some(0),
+            "batch_size must be greater than 0"
+        );
+        ensure!(
+            self.max_pending_batches != Some(0),
+            "max_pending_batches must be greater than 0"
+        );
+        ensure!(
+            self.fetch_tasks != Some(0),
+            "fetch_tasks must be greater than 0"
+        );
+
+        let options = TransactionFetcherOptions::new(
+            self.starting_retry_time_millis,
+            self.max_retry_time_millis,
+            self.batch_size,
+            self.max_pending_batches,
+            self.fetch_tasks.unwrap_or(FETCH_TASKS),
+        );
+        ensure!(
+            options.starting_retry_time_millis <= options.max_retry_time_millis,
+            "starting_retry_time_millis ({}) can't be more than max_retry_time_millis ({})",
+            options.starting_retry_time_millis,
+            options.max_retry_time_millis
+        );
+        Ok(options)
     }
 }
 
@@ -428,6 +549,15 @@ impl TransactionFetcher {
     }
 }
 
+impl Drop for TransactionFetcher {
+    /// Stops the background fetch once nobody is reading batches, rather than letting it panic on a closed channel
+    fn drop(&mut self) {
+        if let Some(fetcher_handle) = self.fetcher_handle.take() {
+            fetcher_handle.abort();
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl TransactionFetcherTrait for TransactionFetcher {
     /// Fetches the next batch based on its internal version counter
@@ -438,7 +568,7 @@ impl TransactionFetcherTrait for TransactionFetcher {
             .expect("No transactions, producer of batches died")
     }
 
-    fn fetch_ledger_info(&mut self) -> LedgerInfo {
+    async fn fetch_ledger_info(&mut self) -> LedgerInfo {
         self.context
             .get_latest_ledger_info_wrapped()
             .unwrap_or_else(|err| panic!("Failed to get ledger info: {}", err))
@@ -478,9 +608,54 @@ impl TransactionFetcherTrait for TransactionFetcher {
 pub trait TransactionFetcherTrait: Send + Sync {
     async fn fetch_next_batch(&mut self) -> Vec<Transaction>;
 
-    fn fetch_ledger_info(&mut self) -> LedgerInfo;
+    async fn fetch_ledger_info(&mut self) -> LedgerInfo;
 
     async fn set_version(&mut self, version: u64);
 
     async fn start(&mut self);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fetcher_options_builder() {
+        let options = TransactionFetcherOptions::builder()
+            .batch_size(100)
+            .fetch_tasks(2)
+            .build()
+            .unwrap();
+        assert_eq!(options.transaction_fetch_batch_size, 100);
+        assert_eq!(options.max_tasks, 2);
+        assert_eq!(options.max_pending_batches, TRANSACTION_CHANNEL_SIZE);
+        assert_eq!(options.starting_retry_time_millis, RETRY_TIME_MILLIS);
+
+        assert!(TransactionFetcherOptions::builder()
+            .batch_size(0)
+            .build()
+            .is_err());
+        assert!(TransactionFetcherOptions::builder()
+            .starting_retry_time_millis(MAX_RETRY_TIME_MILLIS + 1)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_backoff_with_jitter() {
+        let base = Duration::from_millis(100);
+        let cap = Duration::from_millis(1000);
+        for (attempt, expected) in [
+            (0, 100),
+            (1, 200),
+            (2, 400),
+            (3, 800),
+            (4, 1000),
+            (40, 1000),
+        ] {
+            let backoff = backoff_with_jitter(attempt, base, cap);
+            assert!(backoff >= Duration::from_millis(expected / 2));
+            assert!(backoff <= Duration::from_millis(expected));
+        }
+    }
+}
@@ -1,19 +1,36 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
 #[derive(Debug)]
 pub struct ProcessingResult {
     pub name: &'static str,
     pub start_version: u64,
     pub end_version: u64,
+    /// Chain timestamps of the oldest and newest transactions in the batch.
+    /// These are filled in by `process_transactions_with_status`, and stay `None` for batches
+    /// without timestamped transactions (e.g. genesis).
+    pub min_timestamp: Option<chrono::NaiveDateTime>,
+    pub max_timestamp: Option<chrono::NaiveDateTime>,
+    /// Number of rows the batch produced for each table, keyed by table name
+    pub entity_counts: HashMap<&'static str, usize>,
 }
 
 impl ProcessingResult {
-    pub fn new(name: &'static str, start_version: u64, end_version: u64) -> Self {
+    pub fn new(
+        name: &'static str,
+        start_version: u64,
+        end_version: u64,
+        entity_counts: HashMap<&'static str, usize>,
+    ) -> Self {
         Self {
             name,
             start_version,
             end_version,
+            min_timestamp: None,
+            max_timestamp: None,
+            entity_counts,
         }
     }
 }
@@ -0,0 +1,212 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::{FETCHED_TRANSACTION, FETCHER_RETRIES, UNABLE_TO_FETCH_TRANSACTION},
+    indexer::fetcher::{backoff_with_jitter, TransactionFetcherOptions, TransactionFetcherTrait},
+};
+use anyhow::{bail, Context, Result};
+use aptos_api_types::{LedgerInfo, Transaction};
+use aptos_logger::prelude::*;
+use reqwest::StatusCode;
+use url::Url;
+
+/// Part of the message the API answers with when asked for transactions past the tip of the chain
+const PAST_LEDGER_VERSION_MESSAGE: &str = "is higher than the current ledger version";
+
+/// Asking for transactions past the tip of the chain is a 400 rather than an empty page. It only means we've caught up.
+fn is_past_ledger_version(status: StatusCode, body: &str) -> bool {
+    status == StatusCode::BAD_REQUEST && body.contains(PAST_LEDGER_VERSION_MESSAGE)
+}
+
+/// Fetches transactions from a remote Aptos REST API (e.g. a public fullnode) instead of the local node's storage.
+/// Batches are fetched one at a time, so `fetch_tasks` and `fetch_ahead_batches` don't apply.
+#[derive(Debug)]
+pub struct RestTransactionFetcher {
+    client: reqwest::Client,
+    /// Root of the API, including the version prefix, e.g. `https://fullnode.mainnet.aptoslabs.com/v1/`
+    base_url: Url,
+    options: TransactionFetcherOptions,
+    current_version: u64,
+    started: bool,
+}
+
+impl RestTransactionFetcher {
+    pub fn new(base_url: &str, options: TransactionFetcherOptions) -> Result<Self> {
+        // Without a trailing slash, `Url::join` would replace the version prefix instead of appending to it
+        let base_url = if base_url.ends_with('/') {
+            base_url.to_string()
+        } else {
+            format!("{}/", base_url)
+        };
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: Url::parse(&base_url)
+                .with_context(|| format!("Invalid REST fetcher url {}", base_url))?,
+            options,
+            current_version: 0,
+            started: false,
+        })
+    }
+
+    fn transactions_url(&self) -> Url {
+        let mut url = self.base_url.join("transactions").unwrap();
+        url.query_pairs_mut()
+            .append_pair("start", &self.current_version.to_string())
+            .append_pair(
+                "limit",
+                &self.options.transaction_fetch_batch_size.to_string(),
+            );
+        url
+    }
+
+    async fn get_transactions(&self) -> Result<Vec<Transaction>> {
+        let response = self.client.get(self.transactions_url()).send().await?;
+        let status = response.status();
+        if status.is_client_error() {
+            let body = response.text().await?;
+            if is_past_ledger_version(status, &body) {
+                return Ok(vec![]);
+            }
+            bail!("Fetching transactions failed with {}: {}", status, body);
+        }
+        Ok(response
+            .error_for_status()?
+            .json::<Vec<Transaction>>()
+            .await?)
+    }
+
+    async fn get_ledger_info(&self) -> Result<LedgerInfo> {
+        let response = self
+            .client
+            .get(self.base_url.clone())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json::<LedgerInfo>().await?)
+    }
+
+    async fn get_ledger_info_with_retries(&self) -> LedgerInfo {
+        let mut failed_attempts = 0;
+        loop {
+            match self.get_ledger_info().await {
+                Ok(ledger_info) => return ledger_info,
+                Err(err) => {
+                    FETCHER_RETRIES
+                        .with_label_values(&["rest_ledger_info"])
+                        .inc();
+                    error!(
+                        error = format!("{:?}", err),
+                        "Could not fetch ledger info from REST API: retrying"
+                    );
+                    tokio::time::sleep(backoff_with_jitter(
+                        failed_attempts,
+                        self.options.starting_retry_time,
+                        self.options.max_retry_time,
+                    ))
+                    .await;
+                    failed_attempts += 1;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionFetcherTrait for RestTransactionFetcher {
+    /// Keeps polling until there's at least one transaction past the current version. Errors are retried with
+    /// backoff. An empty response, or a start version past the tip of the chain, means we've caught up and is
+    /// polled every `starting_retry_time`.
+    async fn fetch_next_batch(&mut self) -> Vec<Transaction> {
+        let mut failed_attempts = 0;
+        loop {
+            match self.get_transactions().await {
+                Ok(transactions) if !transactions.is_empty() => {
+                    FETCHED_TRANSACTION.inc();
+                    let last_version = transactions.last().unwrap().version().unwrap();
+                    self.current_version = last_version + 1;
+                    return transactions;
+                }
+                Ok(_) => {
+                    failed_attempts = 0;
+                    tokio::time::sleep(self.options.starting_retry_time).await;
+                }
+                Err(err) => {
+                    UNABLE_TO_FETCH_TRANSACTION.inc();
+                    FETCHER_RETRIES
+                        .with_label_values(&["rest_transactions"])
+                        .inc();
+                    error!(
+                        version = self.current_version,
+                        error = format!("{:?}", err),
+                        "Could not fetch transactions from REST API: retrying"
+                    );
+                    tokio::time::sleep(backoff_with_jitter(
+                        failed_attempts,
+                        self.options.starting_retry_time,
+                        self.options.max_retry_time,
+                    ))
+                    .await;
+                    failed_attempts += 1;
+                }
+            }
+        }
+    }
+
+    /// Retries with backoff until the request succeeds
+    async fn fetch_ledger_info(&mut self) -> LedgerInfo {
+        self.get_ledger_info_with_retries().await
+    }
+
+    async fn set_version(&mut self, version: u64) {
+        if self.started {
+            panic!("RestTransactionFetcher already started!");
+        }
+        self.current_version = version;
+    }
+
+    async fn start(&mut self) {
+        if self.started {
+            panic!("RestTransactionFetcher already started!");
+        }
+        self.started = true;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transactions_url() {
+        let options = TransactionFetcherOptions::builder()
+            .batch_size(100)
+            .build()
+            .unwrap();
+        let mut fetcher =
+            RestTransactionFetcher::new("https://fullnode.testnet.aptoslabs.com/v1", options)
+                .unwrap();
+        fetcher.current_version = 42;
+        assert_eq!(
+            fetcher.transactions_url().as_str(),
+            "https://fullnode.testnet.aptoslabs.com/v1/transactions?start=42&limit=100"
+        );
+        assert!(
+            RestTransactionFetcher::new("not a url", TransactionFetcherOptions::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_is_past_ledger_version() {
+        let past_tip = r#"{"message":"Given start value (120) is higher than the current ledger version, it must be < 100","error_code":"invalid_input","vm_error_code":null}"#;
+        assert!(is_past_ledger_version(StatusCode::BAD_REQUEST, past_tip));
+        assert!(!is_past_ledger_version(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            past_tip
+        ));
+        assert!(!is_past_ledger_version(
+            StatusCode::BAD_REQUEST,
+            r#"{"message":"Failed to parse query param: limit","error_code":"web_framework_error","vm_error_code":null}"#
+        ));
+    }
+}
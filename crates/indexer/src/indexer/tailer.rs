@@ -1,7 +1,11 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    database::{execute_with_better_error, PgDbPool},
+    counters::{
+        PROCESSOR_BATCH_TIMEOUTS, PROCESSOR_OVERSIZED_TRANSACTIONS,
+        PROCESSOR_START_VERSION_CLAMPED, PROCESSOR_VERSION_GAPS,
+    },
+    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
     indexer::{
         errors::TransactionProcessingError,
         fetcher::{TransactionFetcher, TransactionFetcherOptions, TransactionFetcherTrait},
@@ -11,30 +15,57 @@ use crate::{
     models::{
         ledger_info::LedgerInfo,
         processor_status::{ProcessorStatusV2, ProcessorStatusV2Query},
+        processor_version_gaps::{
+            ProcessorVersionGap, REASON_OVERSIZED_WRITE_SET, REASON_PARSE_ERROR, REASON_START_GAP,
+        },
     },
-    schema::{ledger_infos, processor_status},
+    schema::{ledger_infos, processor_status, processor_statuses, processor_version_gaps},
 };
 use anyhow::{ensure, Context, Result};
 use aptos_api::context::Context as ApiContext;
-use aptos_logger::{debug, info};
+use aptos_api_types::Transaction;
+use aptos_logger::{debug, error, info, warn};
 use chrono::ParseError;
 use diesel::{
+    dsl::{now, IntervalDsl},
     pg::upsert::excluded,
     sql_query,
     sql_types::{BigInt, Text},
-    ExpressionMethods, RunQueryDsl,
+    ExpressionMethods, QueryDsl, RunQueryDsl,
 };
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::Duration};
 use tokio::{sync::Mutex, task::JoinHandle};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+/// A processor whose status changed within this many seconds is assumed to still be running
+const ACTIVE_PROCESSOR_WINDOW_SECS: i32 = 60;
+/// Key of the advisory lock held while running migrations. Any constant works as long as it's the same everywhere.
+const MIGRATIONS_ADVISORY_LOCK_ID: i64 = 0x696e646578657200;
+
+/// What happened to a single batch in `process_batch`
+#[derive(Debug)]
+pub struct BatchOutcome {
+    pub num_txns: u64,
+    /// How many times the batch timed out and was retried before `result` came back
+    pub retry_count: u64,
+    /// Time spent on the batch, retries included
+    pub elapsed_ms: u64,
+    pub result: Result<ProcessingResult, TransactionProcessingError>,
+}
 
 #[derive(Clone)]
 pub struct Tailer {
     pub transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
     processor: Arc<dyn TransactionProcessor>,
     connection_pool: PgDbPool,
+    /// The local node's API context. Tailers fetching from elsewhere run without one
+    context: Option<Arc<ApiContext>>,
+    fetcher_options: TransactionFetcherOptions,
+    /// If set, transactions are parsed but nothing gets written to the DB. Rows are logged instead.
+    pub dry_run: bool,
+    /// If set, user transactions with more write set changes than this are processed without their write set
+    pub max_writeset_changes_per_txn: Option<usize>,
 }
 
 impl Tailer {
@@ -45,22 +76,146 @@ impl Tailer {
         options: TransactionFetcherOptions,
     ) -> Result<Tailer, ParseError> {
         let resolver = Arc::new(context.move_resolver().unwrap());
-        let transaction_fetcher = TransactionFetcher::new(context, resolver, 0, options);
+        let transaction_fetcher =
+            TransactionFetcher::new(context.clone(), resolver, 0, options.clone());
 
-        Ok(Self {
-            transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
+        let mut tailer = Self::with_fetcher(
+            Arc::new(Mutex::new(transaction_fetcher)),
             connection_pool,
             processor,
-        })
+            options,
+        );
+        tailer.context = Some(context);
+        Ok(tailer)
+    }
+
+    /// A tailer that gets its transactions from `transaction_fetcher` and doesn't need the local node, e.g. to
+    /// fetch from a remote REST API. `reprocess_range` and `clamp_start_version` aren't available on it.
+    pub fn with_fetcher(
+        transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
+        connection_pool: PgDbPool,
+        processor: Arc<dyn TransactionProcessor>,
+        options: TransactionFetcherOptions,
+    ) -> Tailer {
+        Self {
+            transaction_fetcher,
+            connection_pool,
+            processor,
+            context: None,
+            fetcher_options: options,
+            dry_run: false,
+            max_writeset_changes_per_txn: None,
+        }
     }
 
+    fn local_context(&self) -> Result<&Arc<ApiContext>> {
+        self.context
+            .as_ref()
+            .context("This tailer doesn't run against the local node's storage")
+    }
+
+    /// Reprocesses `start_version..=end_version` with a fetcher of its own reading the local node's storage, for
+    /// manual backfills (e.g. after a model changes). Failed batches are logged and recorded in processor_statuses as usual.
+    /// In dry run mode nothing is written, processor_statuses included.
+    /// Returns how many versions were reprocessed successfully.
+    pub async fn reprocess_range(&self, start_version: u64, end_version: u64) -> Result<u64> {
+        ensure!(
+            start_version <= end_version,
+            "Start version {} is after end version {}",
+            start_version,
+            end_version
+        );
+        let context = self.local_context()?;
+        let resolver = Arc::new(context.move_resolver()?);
+        let mut fetcher = TransactionFetcher::new(
+            context.clone(),
+            resolver,
+            start_version,
+            self.fetcher_options.clone(),
+        );
+        fetcher.start().await;
+
+        let mut versions_reprocessed = 0;
+        loop {
+            let mut transactions = fetcher.fetch_next_batch().await;
+            let batch_end_version = transactions.last().unwrap().version().unwrap();
+            transactions.retain(|txn| txn.version().unwrap() <= end_version);
+            let num_txns = transactions.len() as u64;
+            let result = if self.dry_run {
+                self.processor
+                    .process_transactions_dry_run(transactions)
+                    .await
+            } else {
+                self.processor
+                    .process_transactions_with_status(transactions)
+                    .await
+            };
+            match result {
+                Ok(_) => versions_reprocessed += num_txns,
+                Err(tpe) => {
+                    let (err, batch_start, batch_end, _) = tpe.inner();
+                    warn!(
+                        processor_name = self.processor.name(),
+                        start_version = batch_start,
+                        end_version = batch_end,
+                        error = ?err,
+                        "Failed to reprocess batch"
+                    );
+                }
+            }
+            if batch_end_version >= end_version {
+                break;
+            }
+        }
+        info!(
+            processor_name = self.processor.name(),
+            start_version = start_version,
+            end_version = end_version,
+            versions_reprocessed = versions_reprocessed,
+            "Finished reprocessing range"
+        );
+        Ok(versions_reprocessed)
+    }
+
+    /// Runs any pending migrations, logging each one that gets applied.
+    /// Processors sharing a database often start at the same time, so this holds a postgres advisory lock
+    /// while migrating. Whoever gets the lock second will find nothing left to run.
     pub fn run_migrations(&self) {
-        let _ = &self
+        let mut conn = self
             .connection_pool
             .get()
-            .expect("Could not get connection for migrations")
-            .run_pending_migrations(MIGRATIONS)
+            .expect("Could not get connection for migrations");
+        // The pool's statement timeout is sized for batches, so lift it for the lock wait and the migrations.
+        // `SET LOCAL` and the transaction-level lock both end with the transaction.
+        let applied = conn
+            .build_transaction()
+            .run::<_, anyhow::Error, _>(|conn| {
+                sql_query("SET LOCAL statement_timeout = 0").execute(conn)?;
+                sql_query("SELECT pg_advisory_xact_lock($1)")
+                    .bind::<BigInt, _>(MIGRATIONS_ADVISORY_LOCK_ID)
+                    .execute(conn)?;
+                conn.run_pending_migrations(MIGRATIONS)
+                    .map(|versions| {
+                        versions
+                            .iter()
+                            .map(|version| version.to_string())
+                            .collect::<Vec<String>>()
+                    })
+                    .map_err(|e| anyhow::anyhow!(e))
+            })
             .expect("migrations failed!");
+        for version in &applied {
+            info!(
+                processor_name = self.processor.name(),
+                migration = version,
+                "Applied migration"
+            );
+        }
+        info!(
+            processor_name = self.processor.name(),
+            num_applied = applied.len(),
+            "Migrations complete"
+        );
     }
 
     /// If chain id doesn't exist, save it. Otherwise, make sure that we're indexing the same chain
@@ -78,35 +233,43 @@ impl Tailer {
             .lock()
             .await
             .fetch_ledger_info()
+            .await
             .chain_id as i64;
 
-        match maybe_existing_chain_id {
-            Some(chain_id) => {
-                ensure!(chain_id == new_chain_id, "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}", new_chain_id, chain_id);
-                info!(
-                    processor_name = self.processor.name(),
-                    chain_id = chain_id,
-                    "Chain id matches! Continue to index...",
-                );
-                Ok(chain_id as u64)
-            }
-            None => {
-                info!(
-                    processor_name = self.processor.name(),
-                    chain_id = new_chain_id,
-                    "Adding chain id to db, continue to index.."
-                );
-                execute_with_better_error(
-                    &mut conn,
-                    diesel::insert_into(ledger_infos::table).values(LedgerInfo {
+        if maybe_existing_chain_id.is_none() {
+            info!(
+                processor_name = self.processor.name(),
+                chain_id = new_chain_id,
+                "Adding chain id to db, continue to index.."
+            );
+            // Processors sharing the db race to add it on a fresh db. Whoever loses checks against the winner's below.
+            execute_with_better_error(
+                &mut conn,
+                diesel::insert_into(ledger_infos::table)
+                    .values(LedgerInfo {
                         chain_id: new_chain_id,
-                    }),
-                    None,
-                )
-                .context(r#"Error updating chain_id!"#)
-                .map(|_| new_chain_id as u64)
-            }
+                    })
+                    .on_conflict_do_nothing(),
+                None,
+            )
+            .context(r#"Error updating chain_id!"#)?;
         }
+
+        let chain_id = LedgerInfo::get(&mut conn)?
+            .map(|li| li.chain_id)
+            .context("Chain id is missing right after adding it")?;
+        ensure!(
+            chain_id == new_chain_id,
+            "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}",
+            new_chain_id,
+            chain_id
+        );
+        info!(
+            processor_name = self.processor.name(),
+            chain_id = chain_id,
+            "Chain id matches! Continue to index...",
+        );
+        Ok(chain_id as u64)
     }
 
     pub async fn set_fetcher_version(&self, version: u64) {
@@ -118,16 +281,37 @@ impl Tailer {
         info!(version = version, "Will start fetching from version");
     }
 
-    pub async fn process_next_batch(
-        &self,
-    ) -> (u64, Result<ProcessingResult, TransactionProcessingError>) {
-        let transactions = self
+    /// Fetches the next batch to process. Versions past `ending_version` are dropped from the batch;
+    /// returns `None` once there's nothing left to process.
+    pub async fn fetch_next_batch(&self, ending_version: Option<u64>) -> Option<Vec<Transaction>> {
+        let mut transactions = self
             .transaction_fetcher
             .lock()
             .await
             .fetch_next_batch()
             .await;
+        if let Some(ending_version) = ending_version {
+            transactions.retain(|txn| txn.version().unwrap() <= ending_version);
+            if transactions.is_empty() {
+                return None;
+            }
+        }
+        if let Some(max_changes) = self.max_writeset_changes_per_txn {
+            self.handle_oversized_transactions(&mut transactions, max_changes);
+        }
+        Some(transactions)
+    }
 
+    /// Processes a batch. If processing takes longer than `batch_timeout`, the attempt is aborted and the
+    /// same batch is processed again, so a hung batch can't stall the task or get skipped.
+    /// A timed out attempt isn't waited on, since it may be blocked on a query; it's aborted and left to the
+    /// pool's statement timeout. After `max_timeout_retries` retries the task panics instead.
+    pub async fn process_batch(
+        &self,
+        transactions: Vec<Transaction>,
+        batch_timeout: Duration,
+        max_timeout_retries: u64,
+    ) -> BatchOutcome {
         let num_txns = transactions.len() as u64;
         let start_version = transactions.first().unwrap().version();
         let end_version = transactions.last().unwrap().version();
@@ -140,13 +324,65 @@ impl Tailer {
         );
 
         let batch_start = chrono::Utc::now().naive_utc();
+        let mut retry_count = 0;
 
-        let results = self
-            .processor
-            .process_transactions_with_status(transactions)
-            .await;
+        let results = loop {
+            // Processors make blocking DB calls, so the timeout can only fire if processing runs in its own task
+            let processor = self.processor.clone();
+            let batch = transactions.clone();
+            let dry_run = self.dry_run;
+            let mut attempt = tokio::spawn(async move {
+                if dry_run {
+                    processor.process_transactions_dry_run(batch).await
+                } else {
+                    processor.process_transactions_with_status(batch).await
+                }
+            });
+            match tokio::time::timeout(batch_timeout, &mut attempt).await {
+                Ok(Ok(results)) => break results,
+                Ok(Err(join_error)) => std::panic::resume_unwind(join_error.into_panic()),
+                Err(_) => {
+                    PROCESSOR_BATCH_TIMEOUTS
+                        .with_label_values(&[self.processor.name()])
+                        .inc();
+                    // An abort only takes effect at the attempt's next await point, and a blocking query never
+                    // reaches one, so waiting on the attempt here could hang forever. Instead it's detached: the
+                    // pool's statement timeout (see `new_db_pool`) cancels whatever query it's stuck on, and the
+                    // abort stops it at its next await point.
+                    attempt.abort();
+                    drop(attempt);
+                    if retry_count >= max_timeout_retries {
+                        error!(
+                            num_txns = num_txns,
+                            start_version = start_version,
+                            end_version = end_version,
+                            timeout_secs = batch_timeout.as_secs(),
+                            retry_count = retry_count,
+                            "Timed out processing transaction batch, giving up"
+                        );
+                        panic!(
+                            "[Indexer] {} timed out processing versions {:?} to {:?} after {} retries",
+                            self.processor.name(),
+                            start_version,
+                            end_version,
+                            retry_count
+                        );
+                    }
+                    retry_count += 1;
+                    warn!(
+                        num_txns = num_txns,
+                        start_version = start_version,
+                        end_version = end_version,
+                        timeout_secs = batch_timeout.as_secs(),
+                        retry_count = retry_count,
+                        "Timed out processing transaction batch, retrying"
+                    );
+                }
+            }
+        };
 
         let batch_millis = (chrono::Utc::now().naive_utc() - batch_start).num_milliseconds();
+        self.processor.record_latency(batch_millis as u64);
 
         info!(
             num_txns = num_txns,
@@ -156,11 +392,85 @@ impl Tailer {
             "Finished processing of transaction batch"
         );
 
-        (num_txns, results)
+        BatchOutcome {
+            num_txns,
+            retry_count,
+            elapsed_ms: batch_millis as u64,
+            result: results,
+        }
     }
 
-    /// Store last processed version from database. We can assume that all previously processed
-    /// versions are successful because any gap would cause the processor to panic
+    /// Empties the write set of any user transaction with more than `max_changes` changes, so one pathological
+    /// transaction can't blow up the time the processor spends on it or the rows it writes. This doesn't bound
+    /// memory: by now the fetcher has already converted the whole transaction. The rest of the transaction is
+    /// still processed and its version counts as processed, so it's recorded in `processor_version_gaps` with
+    /// reason `oversized_write_set` to tell it apart from versions that were skipped outright and to find it
+    /// when reprocessing.
+    fn handle_oversized_transactions(&self, transactions: &mut [Transaction], max_changes: usize) {
+        let processor_name = self.processor.name();
+        for (version, num_changes) in drop_oversized_write_sets(transactions, max_changes) {
+            warn!(
+                processor_name = processor_name,
+                version = version,
+                num_changes = num_changes,
+                max_changes = max_changes,
+                "Transaction has too many write set changes, processing it without its write set"
+            );
+            PROCESSOR_OVERSIZED_TRANSACTIONS
+                .with_label_values(&[processor_name])
+                .inc();
+            if self.dry_run {
+                continue;
+            }
+            let recorded = self
+                .connection_pool
+                .get()
+                .map_err(anyhow::Error::from)
+                .and_then(|mut conn| {
+                    record_version_gap(
+                        &mut conn,
+                        processor_name,
+                        version,
+                        version,
+                        REASON_OVERSIZED_WRITE_SET,
+                    )
+                });
+            if let Err(err) = recorded {
+                error!(
+                    processor_name = processor_name,
+                    version = version,
+                    error = ?err,
+                    "Failed to record oversized transaction"
+                );
+            }
+        }
+    }
+
+    /// Returns the name of the processor this tailer runs
+    pub fn processor_name(&self) -> &'static str {
+        self.processor.name()
+    }
+
+    /// Records `start_version..=end_version` as a gap because those transactions couldn't be parsed and the
+    /// rest of their batch was processed without them. Nothing is recorded in dry run mode.
+    pub fn record_skipped_versions(&self, start_version: u64, end_version: u64) -> Result<()> {
+        PROCESSOR_VERSION_GAPS
+            .with_label_values(&[self.processor.name()])
+            .inc();
+        if self.dry_run {
+            return Ok(());
+        }
+        record_version_gap(
+            &mut self.connection_pool.get()?,
+            self.processor.name(),
+            start_version,
+            end_version,
+            REASON_PARSE_ERROR,
+        )
+    }
+
+    /// Store last processed version from database. Versions at or before it are either processed or,
+    /// if they had to be skipped, recorded in processor_version_gaps, since any other failure makes the processor panic
     pub fn update_last_processed_version(&self, processor_name: &str, version: u64) -> Result<()> {
         let mut conn = self.connection_pool.get()?;
 
@@ -184,6 +494,64 @@ impl Tailer {
         Ok(())
     }
 
+    /// Rewinds a processor so that it reprocesses from `version` the next time it starts, e.g. after its parsing
+    /// logic is fixed. Drops its per-version statuses from `version` onwards and moves `last_success_version` back.
+    /// Refuses to touch a processor that's still advancing unless `force` is set, since it would just overwrite the reset.
+    /// The runtime calls this at startup, before reading the start version, when `reset_to_version` is configured.
+    pub fn reset_processor_version(
+        &self,
+        processor_name: &str,
+        version: u64,
+        force: bool,
+    ) -> Result<()> {
+        let mut conn = self.connection_pool.get()?;
+
+        if !force {
+            let recently_updated: i64 = processor_status::table
+                .filter(processor_status::processor.eq(processor_name))
+                .filter(
+                    processor_status::last_updated.gt(now - ACTIVE_PROCESSOR_WINDOW_SECS.seconds()),
+                )
+                .count()
+                .get_result(&mut conn)?;
+            ensure!(
+                recently_updated == 0,
+                "Processor {} advanced in the last {}s and may still be running, stop it first or force the reset",
+                processor_name,
+                ACTIVE_PROCESSOR_WINDOW_SECS
+            );
+        }
+
+        conn.build_transaction()
+            .read_write()
+            .run::<_, diesel::result::Error, _>(|pg_conn| {
+                diesel::delete(
+                    processor_statuses::table
+                        .filter(processor_statuses::name.eq(processor_name))
+                        .filter(processor_statuses::version.ge(version as i64)),
+                )
+                .execute(pg_conn)?;
+                // Not an upsert since the usual one never lets the version go backwards
+                diesel::update(
+                    processor_status::table.filter(processor_status::processor.eq(processor_name)),
+                )
+                .set((
+                    processor_status::last_success_version.eq(version as i64 - 1),
+                    processor_status::last_updated.eq(now),
+                ))
+                .execute(pg_conn)?;
+                Ok(())
+            })?;
+
+        warn!(
+            processor_name = processor_name,
+            version = version,
+            force = force,
+            "Reset processor, it will reprocess from this version on its next start"
+        );
+        Ok(())
+    }
+
     /// Get last version processed successfully from databse
     pub fn get_start_version(&self, processor_name: &String) -> Result<Option<i64>> {
         let mut conn = self.connection_pool.get()?;
@@ -194,6 +562,54 @@ impl Tailer {
         }
     }
 
+    /// Makes sure `start_version` is something the node can serve. Versions the node has already pruned can never
+    /// be fetched, so the start is moved up to the first available version instead of retrying forever.
+    /// A start past the node's latest version is left alone since the fetcher waits for the node to catch up,
+    /// but it's logged as it usually means the node's DB was restored from an older backup.
+    pub fn clamp_start_version(&self, processor_name: &str, start_version: u64) -> Result<u64> {
+        let context = self.local_context()?;
+        let first_version = context
+            .db
+            .get_first_viable_txn_version()
+            .context("Failed to get the node's first available version")?;
+        let latest_version = context
+            .db
+            .get_latest_version()
+            .context("Failed to get the node's latest version")?;
+
+        if start_version < first_version {
+            PROCESSOR_START_VERSION_CLAMPED
+                .with_label_values(&[processor_name])
+                .inc();
+            warn!(
+                processor_name = processor_name,
+                start_version = start_version,
+                first_available_version = first_version,
+                "Start version has been pruned from the node, starting from the first available version instead"
+            );
+            return Ok(first_version);
+        }
+        if start_version > latest_version + 1 {
+            warn!(
+                processor_name = processor_name,
+                start_version = start_version,
+                latest_version = latest_version,
+                "Start version is ahead of the node, waiting for it to catch up"
+            );
+        }
+        Ok(start_version)
+    }
+
+    /// Get the latest version this processor has indexed, to compare against the node's ledger version for lag
+    pub fn get_latest_indexed_version(&self, processor_name: &str) -> Result<Option<u64>> {
+        let mut conn = self.connection_pool.get()?;
+
+        Ok(
+            ProcessorStatusV2Query::get_by_processor(&processor_name.to_string(), &mut conn)?
+                .map(|status| status.last_success_version as u64),
+        )
+    }
+
     /// Get starting version from database. Starting version is defined as the first version that's either
     /// not successful or missing from the DB.
     pub fn get_start_version_long(
@@ -296,17 +712,99 @@ pub async fn await_tasks<T: Debug>(tasks: Vec<JoinHandle<T>>) -> Vec<T> {
     results
 }
 
+/// Records a gap if processing is about to start past the version we expected to start at,
+/// i.e. versions `expected_start..actual_start` would never be processed.
+/// Returns whether a gap was found.
+pub fn check_for_gaps(
+    conn: &mut PgPoolConnection,
+    processor_name: &str,
+    expected_start: u64,
+    actual_start: u64,
+) -> Result<bool> {
+    if actual_start <= expected_start {
+        return Ok(false);
+    }
+    error!(
+        processor_name = processor_name,
+        expected_start = expected_start,
+        actual_start = actual_start,
+        "Skipping versions, recording version gap"
+    );
+    PROCESSOR_VERSION_GAPS
+        .with_label_values(&[processor_name])
+        .inc();
+    record_version_gap(
+        conn,
+        processor_name,
+        expected_start,
+        actual_start - 1,
+        REASON_START_GAP,
+    )?;
+    Ok(true)
+}
+
+/// Records that `processor_name` didn't fully process `gap_start_version..=gap_end_version`, and why
+fn record_version_gap(
+    conn: &mut PgPoolConnection,
+    processor_name: &str,
+    gap_start_version: u64,
+    gap_end_version: u64,
+    reason: &str,
+) -> Result<()> {
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(processor_version_gaps::table)
+            .values(ProcessorVersionGap {
+                processor: processor_name.to_string(),
+                gap_start_version: gap_start_version as i64,
+                gap_end_version: gap_end_version as i64,
+                reason: reason.to_string(),
+            })
+            .on_conflict((
+                processor_version_gaps::processor,
+                processor_version_gaps::gap_start_version,
+            ))
+            .do_update()
+            .set((
+                processor_version_gaps::gap_end_version
+                    .eq(excluded(processor_version_gaps::gap_end_version)),
+                processor_version_gaps::reason.eq(excluded(processor_version_gaps::reason)),
+            )),
+        None,
+    )
+    .context("Error recording version gap!")?;
+    Ok(())
+}
+
+/// Clears the write set of every user transaction with more than `max_changes` changes. Genesis is left
+/// alone since its write set is large by design. Returns the version and original size of each one cleared.
+pub fn drop_oversized_write_sets(
+    transactions: &mut [Transaction],
+    max_changes: usize,
+) -> Vec<(u64, usize)> {
+    transactions
+        .iter_mut()
+        .filter_map(|txn| match txn {
+            Transaction::UserTransaction(user_txn) if user_txn.info.changes.len() > max_changes => {
+                let num_changes = user_txn.info.changes.len();
+                user_txn.info.changes.clear();
+                Some((user_txn.info.version.0, num_changes))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
-        database::{new_db_pool, PgPoolConnection},
         models::transactions::TransactionQuery,
-        processors::default_processor::DefaultTransactionProcessor,
+        processors::default_processor::DefaultTransactionProcessor, setup_test_database,
+        TestDatabaseLock,
     };
     use aptos_api_test_context::new_test_context;
-    use aptos_api_types::{LedgerInfo as APILedgerInfo, Transaction, U64};
-    use diesel::RunQueryDsl;
+    use aptos_api_types::{DeleteModule, LedgerInfo as APILedgerInfo, WriteSetChange, U64};
     use serde_json::json;
 
     struct FakeFetcher {
@@ -329,7 +827,7 @@ mod test {
             unimplemented!();
         }
 
-        fn fetch_ledger_info(&mut self) -> APILedgerInfo {
+        async fn fetch_ledger_info(&mut self) -> APILedgerInfo {
             APILedgerInfo {
                 chain_id: self.chain_id,
                 epoch: U64::from(0),
@@ -352,22 +850,8 @@ mod test {
         }
     }
 
-    pub fn wipe_database(conn: &mut PgPoolConnection) {
-        for command in [
-            "DROP SCHEMA public CASCADE",
-            "CREATE SCHEMA public",
-            "GRANT ALL ON SCHEMA public TO postgres",
-            "GRANT ALL ON SCHEMA public TO public",
-        ] {
-            diesel::sql_query(command).execute(conn).unwrap();
-        }
-    }
-
-    pub fn setup_indexer() -> Result<(PgDbPool, Tailer)> {
-        let database_url = std::env::var("INDEXER_DATABASE_URL")
-            .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
-        let conn_pool = new_db_pool(database_url.as_str())?;
-        wipe_database(&mut conn_pool.get()?);
+    pub fn setup_indexer() -> Result<(PgDbPool, Tailer, TestDatabaseLock)> {
+        let (conn_pool, db_lock) = setup_test_database();
 
         let test_context = new_test_context("doesnt_matter".to_string(), true);
         let context: Arc<ApiContext> = Arc::new(test_context.context);
@@ -381,7 +865,7 @@ mod test {
         tailer.transaction_fetcher = Arc::new(Mutex::new(FakeFetcher::new(None)));
         tailer.run_migrations();
 
-        Ok((conn_pool, tailer))
+        Ok((conn_pool, tailer, db_lock))
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -389,7 +873,7 @@ mod test {
         if crate::should_skip_pg_tests() {
             return;
         }
-        let (conn_pool, tailer) = setup_indexer().unwrap();
+        let (conn_pool, tailer, db_lock) = setup_indexer().unwrap();
         // An abridged genesis transaction
         let genesis_txn: Transaction = serde_json::from_value(json!(
             {
@@ -900,7 +1384,9 @@ mod test {
             .await
             .unwrap();
 
-        let (_conn_pool, tailer) = setup_indexer().unwrap();
+        // Starts over on a fresh database, which needs the lock back first
+        drop(db_lock);
+        let (_conn_pool, tailer, _db_lock) = setup_indexer().unwrap();
         tailer.set_fetcher_version(4).await;
         assert!(tailer.check_or_update_chain_id().await.is_ok());
         assert!(tailer.check_or_update_chain_id().await.is_ok());
@@ -911,4 +1397,219 @@ mod test {
         tailer.set_fetcher_version(4).await;
         assert!(tailer.check_or_update_chain_id().await.is_ok());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_latest_indexed_version() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (_conn_pool, tailer, _db_lock) = setup_indexer().unwrap();
+        assert_eq!(
+            tailer.get_latest_indexed_version("test_processor").unwrap(),
+            None
+        );
+
+        tailer
+            .update_last_processed_version("test_processor", 10)
+            .unwrap();
+        tailer
+            .update_last_processed_version("test_processor", 20)
+            .unwrap();
+        assert_eq!(
+            tailer.get_latest_indexed_version("test_processor").unwrap(),
+            Some(20)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reset_processor_version() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (_conn_pool, tailer, _db_lock) = setup_indexer().unwrap();
+        tailer
+            .update_last_processed_version("test_processor", 20)
+            .unwrap();
+
+        // It just advanced, so it looks like it's still running
+        assert!(tailer
+            .reset_processor_version("test_processor", 10, false)
+            .is_err());
+        assert_eq!(
+            tailer
+                .get_start_version(&"test_processor".to_string())
+                .unwrap(),
+            Some(21)
+        );
+
+        tailer
+            .reset_processor_version("test_processor", 10, true)
+            .unwrap();
+        assert_eq!(
+            tailer
+                .get_start_version(&"test_processor".to_string())
+                .unwrap(),
+            Some(10)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_check_for_gaps() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, _tailer, _db_lock) = setup_indexer().unwrap();
+        let mut conn = conn_pool.get().unwrap();
+
+        assert!(!check_for_gaps(&mut conn, "test_processor", 10, 10).unwrap());
+        assert!(!check_for_gaps(&mut conn, "test_processor", 10, 5).unwrap());
+        assert!(check_for_gaps(&mut conn, "test_processor", 10, 20).unwrap());
+
+        let gaps: Vec<(String, i64, i64, chrono::NaiveDateTime, String)> =
+            processor_version_gaps::table.load(&mut conn).unwrap();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!((gaps[0].1, gaps[0].2), (10, 19));
+        assert_eq!(gaps[0].4, REASON_START_GAP);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tailer_without_local_node() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, _db_lock) = setup_test_database();
+        let tailer = Tailer::with_fetcher(
+            Arc::new(Mutex::new(FakeFetcher::new(None))),
+            conn_pool.clone(),
+            Arc::new(DefaultTransactionProcessor::new(conn_pool)),
+            TransactionFetcherOptions::default(),
+        );
+        tailer.set_fetcher_version(4).await;
+        assert_eq!(tailer.check_or_update_chain_id().await.unwrap(), 4);
+        // Only the local node's storage can be checked for pruned versions
+        assert!(tailer.clamp_start_version("test_processor", 0).is_err());
+    }
+
+    /// Blocks on a long `pg_sleep` instead of processing anything, like a batch stuck on a query
+    #[derive(Debug)]
+    struct StallingProcessor {
+        connection_pool: PgDbPool,
+    }
+
+    #[async_trait::async_trait]
+    impl TransactionProcessor for StallingProcessor {
+        fn name(&self) -> &'static str {
+            "stalling_processor"
+        }
+
+        async fn process_transactions(
+            &self,
+            _transactions: Vec<Transaction>,
+            start_version: u64,
+            end_version: u64,
+            _dry_run: bool,
+        ) -> Result<ProcessingResult, TransactionProcessingError> {
+            sql_query("SELECT pg_sleep(5)")
+                .execute(&mut self.get_conn())
+                .unwrap();
+            Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+                Default::default(),
+            ))
+        }
+
+        fn connection_pool(&self) -> &PgDbPool {
+            &self.connection_pool
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_timeout_with_blocking_query() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, _db_lock) = setup_test_database();
+        let tailer = Tailer::with_fetcher(
+            Arc::new(Mutex::new(FakeFetcher::new(None))),
+            conn_pool.clone(),
+            Arc::new(StallingProcessor {
+                connection_pool: conn_pool,
+            }),
+            TransactionFetcherOptions::default(),
+        );
+        let transactions = crate::load_fixture_transactions("marketplace_offer.json");
+
+        // The stalled attempt never yields, so the tailer must give up on it without waiting for the query
+        let started = std::time::Instant::now();
+        let outcome = tokio::spawn(async move {
+            tailer
+                .process_batch(transactions, Duration::from_millis(200), 0)
+                .await
+        })
+        .await;
+        assert!(outcome.unwrap_err().is_panic());
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        // And the pool's statement timeout cancels the query itself
+        let database_url = std::env::var("INDEXER_DATABASE_URL").unwrap();
+        let timeout_pool = crate::database::new_db_pool(
+            &database_url,
+            1,
+            None,
+            aptos_config::config::DEFAULT_DB_CONNECTION_TIMEOUT_SECS,
+            Some(Duration::from_millis(200)),
+        )
+        .unwrap();
+        let result = sql_query("SELECT pg_sleep(10)").execute(&mut timeout_pool.get().unwrap());
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fixture_dry_run() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, tailer, _db_lock) = setup_indexer().unwrap();
+        let result = tailer
+            .processor
+            .process_transactions_dry_run(crate::load_fixture_transactions(
+                "marketplace_offer.json",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result.entity_counts["transactions"], 1);
+        assert_eq!(result.entity_counts["events"], 0);
+
+        // A dry run must not write anything
+        let num_txns: i64 = crate::schema::transactions::table
+            .count()
+            .get_result(&mut conn_pool.get().unwrap())
+            .unwrap();
+        assert_eq!(num_txns, 0);
+    }
+
+    #[test]
+    fn test_drop_oversized_write_sets() {
+        let mut transactions = crate::load_fixture_transactions("marketplace_offer.json");
+        if let Transaction::UserTransaction(user_txn) = &mut transactions[0] {
+            let change = WriteSetChange::DeleteModule(DeleteModule {
+                address: "0x1".parse().unwrap(),
+                state_key_hash: "0x0".to_string(),
+                module: "0x1::coin".parse().unwrap(),
+            });
+            user_txn.info.changes = vec![change; 3];
+        }
+
+        assert!(drop_oversized_write_sets(&mut transactions, 3).is_empty());
+        assert_eq!(
+            drop_oversized_write_sets(&mut transactions, 2),
+            vec![(312056, 3)]
+        );
+        match &transactions[0] {
+            Transaction::UserTransaction(user_txn) => assert!(user_txn.info.changes.is_empty()),
+            _ => panic!("Fixture should be a user transaction"),
+        }
+    }
 }
@@ -5,13 +5,14 @@ use crate::counters::LATEST_PROCESSED_VERSION;
 use crate::database::get_chunks;
 use crate::{
     counters::{
-        GOT_CONNECTION, PROCESSOR_ERRORS, PROCESSOR_INVOCATIONS, PROCESSOR_SUCCESSES,
-        UNABLE_TO_GET_CONNECTION,
+        GOT_CONNECTION, PROCESSOR_BATCH_LATENCY, PROCESSOR_ERRORS, PROCESSOR_INVOCATIONS,
+        PROCESSOR_ROWS_PRODUCED, PROCESSOR_SUCCESSES, UNABLE_TO_GET_CONNECTION,
     },
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
+    database::{execute_with_better_error, PgDbPool, PgPoolConnection, MAX_DIESEL_PARAM_SIZE},
     indexer::{errors::TransactionProcessingError, processing_result::ProcessingResult},
     models::processor_statuses::ProcessorStatusModel,
     schema,
+    util::parse_timestamp,
 };
 use aptos_api_types::Transaction;
 use async_trait::async_trait;
@@ -29,11 +30,13 @@ pub trait TransactionProcessor: Send + Sync + Debug {
 
     /// Process all transactions within a block and processes it. This method will be called from `process_transaction_with_status`
     /// In case a transaction cannot be processed, we will fail the entire block.
+    /// With `dry_run` set, transactions are parsed as usual but the resulting rows are logged instead of written to the DB.
     async fn process_transactions(
         &self,
         transactions: Vec<Transaction>,
         start_version: u64,
         end_version: u64,
+        dry_run: bool,
     ) -> Result<ProcessingResult, TransactionProcessingError>;
 
     /// Gets a reference to the connection pool
@@ -64,6 +67,14 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         }
     }
 
+    /// Records how long a batch took, from being dequeued by a processor task until `process_transactions` returned.
+    /// By default this goes into the per-processor latency histogram.
+    fn record_latency(&self, latency_ms: u64) {
+        PROCESSOR_BATCH_LATENCY
+            .with_label_values(&[self.name()])
+            .observe(latency_ms as f64 / 1000.0);
+    }
+
     /// This is a helper method, tying together the other helper methods to allow tracking status in the DB
     async fn process_transactions_with_status(
         &self,
@@ -79,11 +90,23 @@ pub trait TransactionProcessor: Send + Sync + Debug {
 
         let start_version = txns.first().unwrap().version().unwrap();
         let end_version = txns.last().unwrap().version().unwrap();
+        // Genesis and pending transactions have no timestamp, so skip them
+        let timestamps = txns
+            .iter()
+            .filter(|txn| txn.timestamp() != 0)
+            .map(|txn| parse_timestamp(txn.timestamp(), txn.version().unwrap() as i64));
+        let min_timestamp = timestamps.clone().min();
+        let max_timestamp = timestamps.max();
 
         self.mark_versions_started(start_version, end_version);
         let res = self
-            .process_transactions(txns, start_version, end_version)
-            .await;
+            .process_transactions(txns, start_version, end_version, false)
+            .await
+            .map(|mut processing_result| {
+                processing_result.min_timestamp = min_timestamp;
+                processing_result.max_timestamp = max_timestamp;
+                processing_result
+            });
         // Handle block success/failure
         match res.as_ref() {
             Ok(processing_result) => self.update_status_success(processing_result),
@@ -92,6 +115,22 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         res
     }
 
+    /// Processes transactions without writing anything to the DB, processor status included.
+    /// This is what the `Tailer` calls in dry run mode.
+    async fn process_transactions_dry_run(
+        &self,
+        txns: Vec<Transaction>,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        assert!(
+            !txns.is_empty(),
+            "Must provide at least one transaction to this function"
+        );
+        let start_version = txns.first().unwrap().version().unwrap();
+        let end_version = txns.last().unwrap().version().unwrap();
+        self.process_transactions(txns, start_version, end_version, true)
+            .await
+    }
+
     /// Writes that a version has been started for this `TransactionProcessor` to the DB
     fn mark_versions_started(&self, start_version: u64, end_version: u64) {
         aptos_logger::debug!(
@@ -122,6 +161,11 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         LATEST_PROCESSED_VERSION
             .with_label_values(&[self.name()])
             .set(processing_result.end_version as i64);
+        for (table_name, count) in &processing_result.entity_counts {
+            PROCESSOR_ROWS_PRODUCED
+                .with_label_values(&[self.name(), table_name])
+                .inc_by(*count as u64);
+        }
         let psms = ProcessorStatusModel::from_versions(
             self.name(),
             processing_result.start_version,
@@ -147,7 +191,11 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     /// Actually performs the write for a `ProcessorStatusModel` changeset
     fn apply_processor_status(&self, psms: &[ProcessorStatusModel]) {
         let mut conn = self.get_conn();
-        let chunks = get_chunks(psms.len(), ProcessorStatusModel::field_count());
+        let chunks = get_chunks(
+            psms.len(),
+            ProcessorStatusModel::field_count(),
+            MAX_DIESEL_PARAM_SIZE,
+        );
         for (start_ind, end_ind) in chunks {
             execute_with_better_error(
                 &mut conn,
@@ -33,3 +33,75 @@ pub fn should_skip_pg_tests() -> bool {
         true
     }
 }
+
+/// Loads a JSON array of API transactions from `test_fixtures/`, e.g. transactions recorded from mainnet,
+/// so they can be fed through a processor in tests.
+#[cfg(test)]
+pub fn load_fixture_transactions(fixture_name: &str) -> Vec<aptos_api_types::Transaction> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("test_fixtures")
+        .join(fixture_name);
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read fixture {:?}: {:?}", path, e));
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("Could not parse fixture {:?}: {:?}", path, e))
+}
+
+/// Key of the advisory lock PG tests hold while they use the test database
+#[cfg(test)]
+const TEST_DATABASE_LOCK_ID: i64 = 0x696e6465786572ff;
+
+/// Holds the test database until dropped. All PG tests share `INDEXER_DATABASE_URL` and some of them wipe it,
+/// so each test takes this lock first and they run one at a time. The lock belongs to a connection of its own,
+/// which means a panicking test still releases it.
+#[cfg(test)]
+pub struct TestDatabaseLock {
+    _conn: diesel::PgConnection,
+}
+
+#[cfg(test)]
+pub fn lock_test_database() -> TestDatabaseLock {
+    use diesel::{sql_types::BigInt, Connection, RunQueryDsl};
+
+    let database_url = std::env::var("INDEXER_DATABASE_URL")
+        .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
+    let mut conn = diesel::PgConnection::establish(&database_url)
+        .expect("Could not connect to the test database");
+    diesel::sql_query("SELECT pg_advisory_lock($1)")
+        .bind::<BigInt, _>(TEST_DATABASE_LOCK_ID)
+        .execute(&mut conn)
+        .expect("Could not lock the test database");
+    TestDatabaseLock { _conn: conn }
+}
+
+/// Locks the test database, wipes it and runs the migrations. Keep the lock around for the whole test.
+#[cfg(test)]
+pub fn setup_test_database() -> (database::PgDbPool, TestDatabaseLock) {
+    use aptos_config::config::{DEFAULT_DB_CONNECTION_TIMEOUT_SECS, DEFAULT_DB_POOL_SIZE};
+    use diesel::RunQueryDsl;
+    use diesel_migrations::MigrationHarness;
+
+    let lock = lock_test_database();
+    let database_url = std::env::var("INDEXER_DATABASE_URL")
+        .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
+    let conn_pool = database::new_db_pool(
+        database_url.as_str(),
+        DEFAULT_DB_POOL_SIZE,
+        None,
+        DEFAULT_DB_CONNECTION_TIMEOUT_SECS,
+        None,
+    )
+    .expect("Could not create the test connection pool");
+    let mut conn = conn_pool.get().unwrap();
+    for command in [
+        "DROP SCHEMA public CASCADE",
+        "CREATE SCHEMA public",
+        "GRANT ALL ON SCHEMA public TO postgres",
+        "GRANT ALL ON SCHEMA public TO public",
+    ] {
+        diesel::sql_query(command).execute(&mut conn).unwrap();
+    }
+    conn.run_pending_migrations(indexer::tailer::MIGRATIONS)
+        .unwrap();
+    (conn_pool, lock)
+}
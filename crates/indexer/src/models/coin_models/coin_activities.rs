@@ -12,6 +12,7 @@ use super::{
     coin_utils::{CoinEvent, EventGuidResource},
 };
 use crate::{
+    database::LoggableRow,
     schema::coin_activities,
     util::{parse_timestamp, standardize_address, truncate_str},
 };
@@ -60,6 +61,22 @@ pub struct CoinActivity {
     pub transaction_timestamp: chrono::NaiveDateTime,
 }
 
+impl LoggableRow for CoinActivity {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {}, {})",
+            self.transaction_version,
+            self.event_account_address,
+            self.event_creation_number,
+            self.event_sequence_number
+        )
+    }
+}
+
 impl CoinActivity {
     /// There are different objects containing different information about balances and coins.
     /// Events: Withdraw and Deposit event containing amounts. There is no coin type so we need to get that from Resources. (from event guid)
@@ -70,13 +87,13 @@ impl CoinActivity {
     pub fn from_transaction(
         transaction: &APITransaction,
         maybe_aptos_coin_info: &Option<CoinInfoQuery>,
-    ) -> (
+    ) -> anyhow::Result<(
         Vec<Self>,
         Vec<CoinBalance>,
         HashMap<CoinType, CoinInfo>,
         HashMap<CurrentCoinBalancePK, CurrentCoinBalance>,
         Vec<CoinSupply>,
-    ) {
+    )> {
         let mut coin_activities = Vec::new();
         let mut coin_balances = Vec::new();
         let mut coin_infos: HashMap<CoinType, CoinInfo> = HashMap::new();
@@ -100,7 +117,7 @@ impl CoinActivity {
                 Some(&inner.request),
                 parse_timestamp(inner.timestamp.0, inner.info.version.0 as i64),
             ),
-            _ => return Default::default(),
+            _ => return Ok(Default::default()),
         };
 
         // Get coin info, then coin balances. We can leverage coin balances to get the metadata required for events
@@ -127,14 +144,12 @@ impl CoinActivity {
             let (maybe_coin_info, maybe_coin_balance_data) =
                 if let APIWriteSetChange::WriteResource(write_resource) = wsc {
                     (
-                        CoinInfo::from_write_resource(write_resource, txn_version, txn_timestamp)
-                            .unwrap(),
+                        CoinInfo::from_write_resource(write_resource, txn_version, txn_timestamp)?,
                         CoinBalance::from_write_resource(
                             write_resource,
                             txn_version,
                             txn_timestamp,
-                        )
-                        .unwrap(),
+                        )?,
                     )
                 } else {
                     (None, None)
@@ -147,8 +162,7 @@ impl CoinActivity {
                     txn_version,
                     txn_timestamp,
                     txn_epoch,
-                )
-                .unwrap()
+                )?
             } else {
                 None
             };
@@ -175,7 +189,7 @@ impl CoinActivity {
         }
         for event in events {
             let event_type = event.typ.to_string();
-            match CoinEvent::from_event(event_type.as_str(), &event.data, txn_version).unwrap() {
+            match CoinEvent::from_event(event_type.as_str(), &event.data, txn_version)? {
                 Some(parsed_event) => coin_activities.push(Self::from_parsed_event(
                     &event_type,
                     event,
@@ -189,13 +203,13 @@ impl CoinActivity {
                 None => {}
             };
         }
-        (
+        Ok((
             coin_activities,
             coin_balances,
             coin_infos,
             current_coin_balances,
             all_coin_supply,
-        )
+        ))
     }
 
     fn from_parsed_event(
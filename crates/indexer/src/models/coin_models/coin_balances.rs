@@ -9,7 +9,10 @@ use super::{
     coin_activities::EventToCoinType,
     coin_utils::{CoinInfoType, CoinResource},
 };
-use crate::{schema::coin_balances, schema::current_coin_balances, util::standardize_address};
+use crate::{
+    database::LoggableRow, schema::coin_balances, schema::current_coin_balances,
+    util::standardize_address,
+};
 use aptos_api_types::WriteResource as APIWriteResource;
 use bigdecimal::BigDecimal;
 use field_count::FieldCount;
@@ -28,6 +31,19 @@ pub struct CoinBalance {
     pub transaction_timestamp: chrono::NaiveDateTime,
 }
 
+impl LoggableRow for CoinBalance {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {})",
+            self.transaction_version, self.owner_address, self.coin_type
+        )
+    }
+}
+
 #[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(owner_address, coin_type))]
 #[diesel(table_name = current_coin_balances)]
@@ -40,6 +56,16 @@ pub struct CurrentCoinBalance {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
 }
 
+impl LoggableRow for CurrentCoinBalance {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!("({}, {})", self.owner_address, self.coin_type)
+    }
+}
+
 impl CoinBalance {
     /// We can find coin info from resources. If the coin info appears multiple times we will only keep the first transaction because it can't be modified.
     pub fn from_write_resource(
@@ -6,7 +6,10 @@
 #![allow(clippy::unused_unit)]
 
 use super::coin_utils::{CoinInfoType, CoinResource};
-use crate::{database::PgPoolConnection, schema::coin_infos};
+use crate::{
+    database::{LoggableRow, PgPoolConnection},
+    schema::coin_infos,
+};
 use aptos_api_types::WriteResource as APIWriteResource;
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 use field_count::FieldCount;
@@ -28,6 +31,16 @@ pub struct CoinInfo {
     pub supply_aggregator_table_key: Option<String>,
 }
 
+impl LoggableRow for CoinInfo {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version_created
+    }
+
+    fn pk(&self) -> String {
+        self.coin_type_hash.to_string()
+    }
+}
+
 #[derive(Debug, Deserialize, Identifiable, Queryable, Serialize)]
 #[diesel(primary_key(coin_type_hash))]
 #[diesel(table_name = coin_infos)]
@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 #![allow(clippy::extra_unused_lifetimes)]
-use crate::{models::transactions::Transaction, schema::events, util::standardize_address};
+use crate::{
+    database::LoggableRow, models::transactions::Transaction, schema::events,
+    util::standardize_address,
+};
 use aptos_api_types::Event as APIEvent;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
@@ -22,6 +25,19 @@ pub struct Event {
     pub data: serde_json::Value,
 }
 
+impl LoggableRow for Event {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {})",
+            self.account_address, self.creation_number, self.sequence_number
+        )
+    }
+}
+
 /// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
 #[derive(Associations, Debug, Deserialize, Identifiable, Queryable, Serialize)]
 #[diesel(belongs_to(TransactionQuery, foreign_key = transaction_version))]
@@ -2,42 +2,87 @@
 #![allow(clippy::unused_unit)]
 
 use aptos_api_types::{TransactionPayload, UserTransaction};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::{schema::marketplace_bids, util::parse_timestamp};
+use super::{integer_argument, names_encoded, string_argument};
+use crate::{database::PgPoolConnection, schema::marketplace_bids, util::parse_timestamp};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Queryable, Serialize)]
-#[diesel(primary_key(creator_address, collection_name))]
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(token_name, property_version, price, maker))]
 #[diesel(table_name = marketplace_bids)]
 pub struct MarketplaceBids {
-    creator_address: String,
-    collection_name: String,
-    token_name: String,
-    property_version: i32,
-    price: i64,
-    maker: String,
-    timestamp: chrono::NaiveDateTime,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: String,
+    pub property_version: i32,
+    pub price: i64,
+    pub maker: String,
+    pub timestamp: chrono::NaiveDateTime,
+    /// Version of the transaction that last wrote this row
+    pub txn_version: i64,
+    pub names_encoded: bool,
+}
+
+#[derive(Debug, Identifiable, Queryable)]
+#[diesel(primary_key(token_name, property_version, price, maker))]
+#[diesel(table_name = marketplace_bids)]
+pub struct MarketplaceBidsQuery {
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: String,
+    pub property_version: i32,
+    pub price: i64,
+    pub maker: String,
+    pub timestamp: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub txn_version: i64,
+    pub names_encoded: bool,
 }
 
 impl MarketplaceBids {
     pub fn from_transaction(txn: &UserTransaction) -> Option<Self> {
         let version = txn.info.version.0;
-        match txn.request.payload {
+        match &txn.request.payload {
+            // Everything is read from the first argument, so a call where it's missing a field, or has one of the
+            // wrong type, isn't a marketplace call
             TransactionPayload::EntryFunctionPayload(payload) => Some(Self {
-                creator_address: payload.arguments[0]["creator"].to_string(),
-                collection_name: payload.arguments[0]["collection_name"].to_string(),
-                token_name: payload.arguments[0]["token_name"].to_string(),
-                property_version: payload.arguments[0]["property_version"]
-                    .as_i64()
-                    .unwrap()
+                creator_address: string_argument(payload, "creator")?,
+                collection_name: string_argument(payload, "collection_name")?,
+                token_name: string_argument(payload, "token_name")?,
+                property_version: integer_argument(payload, "property_version")?
                     .try_into()
-                    .unwrap(),
-                price: payload.arguments[0]["price"].as_i64().unwrap(),
+                    .ok()?,
+                price: integer_argument(payload, "price")?,
                 maker: txn.request.sender.inner().to_hex_literal(),
                 timestamp: parse_timestamp(txn.timestamp.0, version.try_into().unwrap()),
+                txn_version: version as i64,
+                names_encoded: names_encoded(
+                    payload,
+                    &["creator", "collection_name", "token_name"],
+                ),
             }),
             _ => None,
         }
     }
+
+    /// The best bid a seller can accept for this token, if there is any.
+    pub fn highest_for_token(
+        conn: &mut PgPoolConnection,
+        creator_address: &str,
+        collection_name: &str,
+        token_name: &str,
+        property_version: i32,
+    ) -> diesel::QueryResult<Option<MarketplaceBidsQuery>> {
+        marketplace_bids::table
+            .filter(marketplace_bids::creator_address.eq(creator_address))
+            .filter(marketplace_bids::collection_name.eq(collection_name))
+            .filter(marketplace_bids::token_name.eq(token_name))
+            .filter(marketplace_bids::property_version.eq(property_version))
+            .order_by(marketplace_bids::price.desc())
+            .first::<MarketplaceBidsQuery>(conn)
+            .optional()
+    }
 }
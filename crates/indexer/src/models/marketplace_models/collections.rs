@@ -2,30 +2,67 @@
 #![allow(clippy::unused_unit)]
 
 use aptos_api_types::{TransactionPayload, UserTransaction};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::{schema::marketplace_collections, util::parse_timestamp};
+use super::{names_encoded, string_argument};
+use crate::{database::PgPoolConnection, schema::marketplace_collections, util::parse_timestamp};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Queryable, Serialize)]
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(creator_address, collection_name))]
 #[diesel(table_name = marketplace_collections)]
 pub struct MarketplaceCollection {
-    creator_address: String,
-    collection_name: String,
-    creation_timestamp: chrono::NaiveDateTime,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub creation_timestamp: chrono::NaiveDateTime,
+    /// Version of the transaction that last wrote this row
+    pub txn_version: i64,
+    pub names_encoded: bool,
+}
+
+#[derive(Debug, Identifiable, Queryable)]
+#[diesel(primary_key(creator_address, collection_name))]
+#[diesel(table_name = marketplace_collections)]
+pub struct MarketplaceCollectionQuery {
+    pub creator_address: String,
+    pub collection_name: String,
+    pub creation_timestamp: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub txn_version: i64,
+    pub names_encoded: bool,
 }
 
 impl MarketplaceCollection {
     pub fn from_transaction(txn: &UserTransaction) -> Option<Self> {
         let version = txn.info.version.0;
-        match txn.request.payload {
+        match &txn.request.payload {
+            // Everything is read from the first argument, so a call where it's missing a field, or has one of the
+            // wrong type, isn't a marketplace call
             TransactionPayload::EntryFunctionPayload(payload) => Some(Self {
-                creator_address: payload.arguments[0]["creator"].to_string(),
-                collection_name: payload.arguments[0]["collection_name"].to_string(),
+                creator_address: string_argument(payload, "creator")?,
+                collection_name: string_argument(payload, "collection_name")?,
                 creation_timestamp: parse_timestamp(txn.timestamp.0, version.try_into().unwrap()),
+                txn_version: version as i64,
+                names_encoded: names_encoded(payload, &["creator", "collection_name"]),
             }),
             _ => None,
         }
     }
+
+    /// Collections created by `creator_address`, ordered by name so pages are stable.
+    pub fn list_for_creator(
+        conn: &mut PgPoolConnection,
+        creator_address: &str,
+        limit: i64,
+        offset: i64,
+    ) -> diesel::QueryResult<Vec<MarketplaceCollectionQuery>> {
+        marketplace_collections::table
+            .filter(marketplace_collections::creator_address.eq(creator_address))
+            .order(marketplace_collections::collection_name.asc())
+            .limit(limit)
+            .offset(offset)
+            .load::<MarketplaceCollectionQuery>(conn)
+    }
 }
@@ -2,3 +2,40 @@ pub mod bids;
 pub mod collections;
 pub mod offers;
 pub mod orders;
+
+use crate::{counters::SANITIZED_NAMES, util::sanitize_name};
+use aptos_api_types::EntryFunctionPayload;
+
+/// Reads a string field from the first argument of a marketplace entry function, or `None` if it's missing
+/// or isn't a string. `serde_json::Value::to_string` would keep the JSON quotes around the value.
+/// Values are escaped the same way as token names, since they're mostly names.
+pub(crate) fn string_argument(payload: &EntryFunctionPayload, key: &str) -> Option<String> {
+    let value = payload.arguments.first()?.get(key)?.as_str()?;
+    let (value, escaped) = sanitize_name(value);
+    if escaped {
+        SANITIZED_NAMES.with_label_values(&["marketplace"]).inc();
+    }
+    Some(value)
+}
+
+/// Whether any of `keys` is escaped by `string_argument`. Stored with each row so readers know which values
+/// to unescape.
+pub(crate) fn names_encoded(payload: &EntryFunctionPayload, keys: &[&str]) -> bool {
+    keys.iter().any(|key| {
+        payload
+            .arguments
+            .first()
+            .and_then(|argument| argument.get(key))
+            .and_then(|value| value.as_str())
+            .map_or(false, |value| sanitize_name(value).1)
+    })
+}
+
+/// Reads an integer field from the first argument of a marketplace entry function, or `None` if it's missing
+/// or isn't an integer. The API encodes u64s as strings, so numeric strings are accepted too.
+pub(crate) fn integer_argument(payload: &EntryFunctionPayload, key: &str) -> Option<i64> {
+    let value = payload.arguments.first()?.get(key)?;
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|value| value.parse().ok()))
+}
@@ -2,42 +2,98 @@
 #![allow(clippy::unused_unit)]
 
 use aptos_api_types::{TransactionPayload, UserTransaction};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::{schema::marketplace_offers, util::parse_timestamp};
+use super::{integer_argument, names_encoded, string_argument};
+use crate::{database::PgPoolConnection, schema::marketplace_offers, util::parse_timestamp};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Queryable, Serialize)]
-#[diesel(primary_key(creator_address, collection_name))]
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(creator_address, collection_name, token_name, property_version))]
 #[diesel(table_name = marketplace_offers)]
 pub struct MarketplaceOffer {
-    creator_address: String,
-    collection_name: String,
-    token_name: String,
-    property_version: i32,
-    price: i64,
-    seller: String,
-    timestamp: chrono::NaiveDateTime,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: String,
+    pub property_version: i32,
+    pub price: i64,
+    pub seller: String,
+    pub timestamp: chrono::NaiveDateTime,
+    /// Version of the transaction that last wrote this row
+    pub txn_version: i64,
+    pub names_encoded: bool,
+}
+
+#[derive(Debug, Identifiable, Queryable)]
+#[diesel(primary_key(creator_address, collection_name, token_name, property_version))]
+#[diesel(table_name = marketplace_offers)]
+pub struct MarketplaceOfferQuery {
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: String,
+    pub property_version: i32,
+    pub price: i64,
+    pub seller: String,
+    pub timestamp: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub txn_version: i64,
+    pub names_encoded: bool,
 }
 
 impl MarketplaceOffer {
     pub fn from_transaction(txn: &UserTransaction) -> Option<Self> {
         let version = txn.info.version.0;
-        match txn.request.payload {
+        match &txn.request.payload {
+            // Everything is read from the first argument, so a call where it's missing a field, or has one of the
+            // wrong type, isn't a marketplace call
             TransactionPayload::EntryFunctionPayload(payload) => Some(Self {
-                creator_address: payload.arguments[0]["creator"].to_string(),
-                collection_name: payload.arguments[0]["collection_name"].to_string(),
-                token_name: payload.arguments[0]["token_name"].to_string(),
-                property_version: payload.arguments[0]["property_version"]
-                    .as_i64()
-                    .unwrap()
+                creator_address: string_argument(payload, "creator")?,
+                collection_name: string_argument(payload, "collection_name")?,
+                token_name: string_argument(payload, "token_name")?,
+                property_version: integer_argument(payload, "property_version")?
                     .try_into()
-                    .unwrap(),
-                price: payload.arguments[0]["price"].as_i64().unwrap(),
+                    .ok()?,
+                price: integer_argument(payload, "price")?,
                 seller: txn.request.sender.inner().to_hex_literal(),
                 timestamp: parse_timestamp(txn.timestamp.0, version.try_into().unwrap()),
+                txn_version: version as i64,
+                names_encoded: names_encoded(
+                    payload,
+                    &["creator", "collection_name", "token_name"],
+                ),
             }),
             _ => None,
         }
     }
+
+    pub fn for_token(
+        conn: &mut PgPoolConnection,
+        creator_address: &str,
+        collection_name: &str,
+        token_name: &str,
+        property_version: i32,
+    ) -> diesel::QueryResult<Vec<MarketplaceOfferQuery>> {
+        marketplace_offers::table
+            .filter(marketplace_offers::creator_address.eq(creator_address))
+            .filter(marketplace_offers::collection_name.eq(collection_name))
+            .filter(marketplace_offers::token_name.eq(token_name))
+            .filter(marketplace_offers::property_version.eq(property_version))
+            .load::<MarketplaceOfferQuery>(conn)
+    }
+
+    /// The floor offer of a collection, if anything in it is listed.
+    pub fn cheapest_for_collection(
+        conn: &mut PgPoolConnection,
+        creator_address: &str,
+        collection_name: &str,
+    ) -> diesel::QueryResult<Option<MarketplaceOfferQuery>> {
+        marketplace_offers::table
+            .filter(marketplace_offers::creator_address.eq(creator_address))
+            .filter(marketplace_offers::collection_name.eq(collection_name))
+            .order(marketplace_offers::price.asc())
+            .first::<MarketplaceOfferQuery>(conn)
+            .optional()
+    }
 }
@@ -5,39 +5,66 @@ use aptos_api_types::{TransactionPayload, UserTransaction};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
+use super::{integer_argument, names_encoded, string_argument};
 use crate::{schema::marketplace_orders, util::parse_timestamp};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Queryable, Serialize)]
-#[diesel(primary_key(creator_address, collection_name))]
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(token_name, property_version, price, quantity, maker))]
 #[diesel(table_name = marketplace_orders)]
 pub struct MarketplaceOrder {
-    creator_address: String,
-    collection_name: String,
-    token_name: String,
-    property_version: i32,
-    price: i64,
-    quantity: i64,
-    maker: String,
-    timestamp: chrono::NaiveDateTime,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: String,
+    pub property_version: i32,
+    pub price: i64,
+    pub quantity: i64,
+    pub maker: String,
+    pub timestamp: chrono::NaiveDateTime,
+    /// Version of the transaction that last wrote this row
+    pub txn_version: i64,
+    pub names_encoded: bool,
+}
+
+#[derive(Debug, Identifiable, Queryable)]
+#[diesel(primary_key(token_name, property_version, price, quantity, maker))]
+#[diesel(table_name = marketplace_orders)]
+pub struct MarketplaceOrderQuery {
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: String,
+    pub property_version: i32,
+    pub price: i64,
+    pub quantity: i64,
+    pub maker: String,
+    pub timestamp: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub txn_version: i64,
+    pub names_encoded: bool,
 }
 
 impl MarketplaceOrder {
     pub fn from_transaction(txn: &UserTransaction) -> Option<Self> {
         let version = txn.info.version.0;
-        match txn.request.payload {
+        match &txn.request.payload {
+            // Everything is read from the first argument, so a call where it's missing a field, or has one of the
+            // wrong type, isn't a marketplace call
             TransactionPayload::EntryFunctionPayload(payload) => Some(Self {
-                creator_address: payload.arguments[0]["creator"].to_string(),
-                collection_name: payload.arguments[0]["collection_name"].to_string(),
-                token_name: payload.arguments[0]["token_name"].to_string(),
-                property_version: payload.arguments[0]["property_version"]
-                    .as_i64()
-                    .unwrap()
+                creator_address: string_argument(payload, "creator")?,
+                collection_name: string_argument(payload, "collection_name")?,
+                token_name: string_argument(payload, "token_name")?,
+                property_version: integer_argument(payload, "property_version")?
                     .try_into()
-                    .unwrap(),
-                price: payload.arguments[0]["price"].as_i64().unwrap(),
-                quantity: payload.arguments[0]["quantity"].as_i64().unwrap(),
+                    .ok()?,
+                price: integer_argument(payload, "price")?,
+                quantity: integer_argument(payload, "quantity")?,
                 maker: txn.request.sender.inner().to_hex_literal(),
                 timestamp: parse_timestamp(txn.timestamp.0, version.try_into().unwrap()),
+                txn_version: version as i64,
+                names_encoded: names_encoded(
+                    payload,
+                    &["creator", "collection_name", "token_name"],
+                ),
             }),
             _ => None,
         }
@@ -11,6 +11,7 @@ pub mod move_resources;
 pub mod move_tables;
 pub mod processor_status;
 pub mod processor_statuses;
+pub mod processor_version_gaps;
 pub mod signatures;
 pub mod stake_models;
 pub mod token_models;
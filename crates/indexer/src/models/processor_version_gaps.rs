@@ -0,0 +1,23 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+#![allow(clippy::extra_unused_lifetimes)]
+use crate::schema::processor_version_gaps;
+
+/// The processor was started past the version after its last processed one
+pub const REASON_START_GAP: &str = "start_gap";
+/// The transactions couldn't be parsed, so their batch was processed without them
+pub const REASON_PARSE_ERROR: &str = "parse_error";
+/// The user transaction had more than `max_writeset_changes_per_txn` write set changes, so it was processed
+/// without its write set. Unlike the other reasons, the version itself does count as processed.
+pub const REASON_OVERSIZED_WRITE_SET: &str = "oversized_write_set";
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = processor_version_gaps)]
+/// A range of versions (inclusive) that a processor skipped over, or only partly processed
+pub struct ProcessorVersionGap {
+    pub processor: String,
+    pub gap_start_version: i64,
+    pub gap_end_version: i64,
+    /// One of the `REASON_*` constants
+    pub reason: String,
+}
@@ -5,7 +5,7 @@
 #![allow(clippy::extra_unused_lifetimes)]
 
 use super::stake_utils::StakeResource;
-use crate::{schema::current_staking_pool_voter, util::standardize_address};
+use crate::{database::LoggableRow, schema::current_staking_pool_voter, util::standardize_address};
 use aptos_api_types::{Transaction as APITransaction, WriteSetChange as APIWriteSetChange};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,16 @@ pub struct CurrentStakingPoolVoter {
     pub last_transaction_version: i64,
 }
 
+impl LoggableRow for CurrentStakingPoolVoter {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        self.staking_pool_address.to_string()
+    }
+}
+
 impl CurrentStakingPoolVoter {
     pub fn from_transaction(transaction: &APITransaction) -> anyhow::Result<StakingPoolVoterMap> {
         let mut staking_pool_voters = HashMap::new();
@@ -8,6 +8,7 @@
 use std::collections::HashMap;
 
 use crate::{
+    database::LoggableRow,
     schema::current_ans_lookup,
     util::{bigdecimal_to_u64, parse_timestamp_secs, standardize_address},
 };
@@ -33,6 +34,16 @@ pub struct CurrentAnsLookup {
     pub expiration_timestamp: chrono::NaiveDateTime,
 }
 
+impl LoggableRow for CurrentAnsLookup {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!("({}, {})", self.domain, self.subdomain)
+    }
+}
+
 pub enum ANSEvent {
     SetNameAddressEventV1(SetNameAddressEventV1),
     RegisterNameEventV1(RegisterNameEventV1),
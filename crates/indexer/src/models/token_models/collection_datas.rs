@@ -10,7 +10,7 @@ use super::{
     tokens::TableHandleToOwner,
 };
 use crate::{
-    database::PgPoolConnection,
+    database::{LoggableRow, PgPoolConnection},
     schema::{collection_datas, current_collection_datas},
     util::standardize_address,
 };
@@ -40,6 +40,20 @@ pub struct CollectionData {
     pub description_mutable: bool,
     pub table_handle: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for CollectionData {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {})",
+            self.collection_data_id_hash, self.transaction_version
+        )
+    }
 }
 
 #[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
@@ -59,6 +73,17 @@ pub struct CurrentCollectionData {
     pub last_transaction_version: i64,
     pub table_handle: String,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for CurrentCollectionData {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        self.collection_data_id_hash.to_string()
+    }
 }
 
 /// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
@@ -80,6 +105,7 @@ pub struct CurrentCollectionDataQuery {
     pub inserted_at: chrono::NaiveDateTime,
     pub table_handle: String,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
 }
 
 impl CollectionData {
@@ -117,6 +143,7 @@ impl CollectionData {
                 CollectionDataIdType::new(creator_address, collection_data.get_name().to_string());
             let collection_data_id_hash = collection_data_id.to_hash();
             let collection_name = collection_data.get_name_trunc();
+            let names_encoded = collection_data.names_encoded();
             let metadata_uri = collection_data.get_uri_trunc();
 
             Ok(Some((
@@ -134,6 +161,7 @@ impl CollectionData {
                     description_mutable: collection_data.mutability_config.description,
                     table_handle: table_handle.clone(),
                     transaction_timestamp: txn_timestamp,
+                    names_encoded,
                 },
                 CurrentCollectionData {
                     collection_data_id_hash,
@@ -149,6 +177,7 @@ impl CollectionData {
                     last_transaction_version: txn_version,
                     table_handle,
                     last_transaction_timestamp: txn_timestamp,
+                    names_encoded,
                 },
             )))
         } else {
@@ -164,16 +193,19 @@ impl CollectionData {
         table_handle: &str,
     ) -> anyhow::Result<String> {
         let mut retried = 0;
-        while retried < QUERY_RETRIES {
+        loop {
             retried += 1;
             match CurrentCollectionDataQuery::get_by_table_handle(conn, table_handle) {
                 Ok(current_collection_data) => return Ok(current_collection_data.creator_address),
+                // Keep the diesel error so the processor can tell this apart from a parse failure
+                Err(err) if retried >= QUERY_RETRIES => {
+                    return Err(anyhow::Error::from(err).context("Failed to get collection creator"))
+                }
                 Err(_) => {
                     std::thread::sleep(std::time::Duration::from_millis(QUERY_RETRY_DELAY_MS));
                 }
             }
         }
-        Err(anyhow::anyhow!("Failed to get collection creator"))
     }
 }
 
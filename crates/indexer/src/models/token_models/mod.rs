@@ -7,5 +7,6 @@ pub mod token_activities;
 pub mod token_claims;
 pub mod token_datas;
 pub mod token_ownerships;
+pub mod token_properties;
 pub mod token_utils;
 pub mod tokens;
@@ -7,6 +7,7 @@
 
 use super::token_utils::{TokenDataIdType, TokenEvent};
 use crate::{
+    database::LoggableRow,
     schema::token_activities,
     util::{parse_timestamp, standardize_address},
 };
@@ -41,6 +42,23 @@ pub struct TokenActivity {
     pub coin_amount: Option<BigDecimal>,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for TokenActivity {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {}, {})",
+            self.transaction_version,
+            self.event_account_address,
+            self.event_creation_number,
+            self.event_sequence_number
+        )
+    }
 }
 
 /// A simplified TokenActivity (excluded common fields) to reduce code duplication
@@ -55,14 +73,13 @@ struct TokenActivityHelper<'a> {
 }
 
 impl TokenActivity {
-    pub fn from_transaction(transaction: &APITransaction) -> Vec<Self> {
+    pub fn from_transaction(transaction: &APITransaction) -> anyhow::Result<Vec<Self>> {
         let mut token_activities = vec![];
         if let APITransaction::UserTransaction(user_txn) = transaction {
             for event in &user_txn.events {
                 let txn_version = user_txn.info.version.0 as i64;
                 let event_type = event.typ.to_string();
-                match TokenEvent::from_event(event_type.as_str(), &event.data, txn_version).unwrap()
-                {
+                match TokenEvent::from_event(event_type.as_str(), &event.data, txn_version)? {
                     Some(token_event) => token_activities.push(Self::from_parsed_event(
                         &event_type,
                         event,
@@ -74,7 +91,7 @@ impl TokenActivity {
                 };
             }
         }
-        token_activities
+        Ok(token_activities)
     }
 
     pub fn from_parsed_event(
@@ -172,6 +189,7 @@ impl TokenActivity {
             creator_address: standardize_address(&token_data_id.creator),
             collection_name: token_data_id.get_collection_trunc(),
             name: token_data_id.get_name_trunc(),
+            names_encoded: token_data_id.names_encoded(),
             transaction_version: txn_version,
             transfer_type: event_type.to_string(),
             from_address: token_activity_helper.from_address,
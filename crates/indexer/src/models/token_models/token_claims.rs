@@ -6,7 +6,9 @@
 #![allow(clippy::unused_unit)]
 
 use super::{token_utils::TokenWriteSet, tokens::TableHandleToOwner};
-use crate::{schema::current_token_pending_claims, util::standardize_address};
+use crate::{
+    database::LoggableRow, schema::current_token_pending_claims, util::standardize_address,
+};
 use aptos_api_types::{DeleteTableItem as APIDeleteTableItem, WriteTableItem as APIWriteTableItem};
 use bigdecimal::{BigDecimal, Zero};
 use field_count::FieldCount;
@@ -28,6 +30,20 @@ pub struct CurrentTokenPendingClaim {
     pub table_handle: String,
     pub last_transaction_version: i64,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for CurrentTokenPendingClaim {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {}, {})",
+            self.token_data_id_hash, self.property_version, self.from_address, self.to_address
+        )
+    }
 }
 
 impl CurrentTokenPendingClaim {
@@ -70,6 +86,7 @@ impl CurrentTokenPendingClaim {
                     let token_data_id_hash = token_data_id.to_hash();
                     let collection_name = token_data_id.get_collection_trunc();
                     let name = token_data_id.get_name_trunc();
+                    let names_encoded = token_data_id.names_encoded();
 
                     return Ok(Some(Self {
                         token_data_id_hash,
@@ -84,6 +101,7 @@ impl CurrentTokenPendingClaim {
                         table_handle,
                         last_transaction_version: txn_version,
                         last_transaction_timestamp: txn_timestamp,
+                        names_encoded,
                     }));
                 } else {
                     aptos_logger::warn!(
@@ -138,6 +156,7 @@ impl CurrentTokenPendingClaim {
             let token_data_id_hash = token_data_id.to_hash();
             let collection_name = token_data_id.get_collection_trunc();
             let name = token_data_id.get_name_trunc();
+            let names_encoded = token_data_id.names_encoded();
 
             return Ok(Some(Self {
                 token_data_id_hash,
@@ -152,6 +171,7 @@ impl CurrentTokenPendingClaim {
                 table_handle,
                 last_transaction_version: txn_version,
                 last_transaction_timestamp: txn_timestamp,
+                names_encoded,
             }));
         }
         Ok(None)
@@ -7,6 +7,7 @@
 
 use super::token_utils::TokenWriteSet;
 use crate::{
+    database::LoggableRow,
     schema::{current_token_datas, token_datas},
     util::standardize_address,
 };
@@ -40,6 +41,20 @@ pub struct TokenData {
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub description: String,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for TokenData {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {})",
+            self.token_data_id_hash, self.transaction_version
+        )
+    }
 }
 
 #[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
@@ -67,6 +82,17 @@ pub struct CurrentTokenData {
     pub collection_data_id_hash: String,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
     pub description: String,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for CurrentTokenData {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        self.token_data_id_hash.to_string()
+    }
 }
 
 impl TokenData {
@@ -100,6 +126,7 @@ impl TokenData {
                 let token_data_id_hash = token_data_id.to_hash();
                 let collection_name = token_data_id.get_collection_trunc();
                 let name = token_data_id.get_name_trunc();
+                let names_encoded = token_data_id.names_encoded();
                 let metadata_uri = token_data.get_uri_trunc();
 
                 return Ok(Some((
@@ -131,6 +158,7 @@ impl TokenData {
                         default_properties: token_data.default_properties.clone(),
                         transaction_timestamp: txn_timestamp,
                         description: token_data.description.clone(),
+                        names_encoded,
                     },
                     CurrentTokenData {
                         collection_data_id_hash,
@@ -154,6 +182,7 @@ impl TokenData {
                         last_transaction_version: txn_version,
                         last_transaction_timestamp: txn_timestamp,
                         description: token_data.description,
+                        names_encoded,
                     },
                 )));
             } else {
@@ -10,11 +10,12 @@ use super::{
     tokens::{TableHandleToOwner, Token},
 };
 use crate::{
+    database::{LoggableRow, PgPoolConnection},
     schema::{current_token_ownerships, token_ownerships},
     util::standardize_address,
 };
-use bigdecimal::BigDecimal;
-use diesel::{query_dsl::methods::FilterDsl, QueryDsl};
+use bigdecimal::{BigDecimal, Zero};
+use diesel::prelude::*;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -40,6 +41,23 @@ pub struct TokenOwnership {
     pub table_type: Option<String>,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for TokenOwnership {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {}, {})",
+            self.token_data_id_hash,
+            self.property_version,
+            self.transaction_version,
+            self.table_handle
+        )
+    }
 }
 
 #[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
@@ -58,6 +76,41 @@ pub struct CurrentTokenOwnership {
     pub collection_data_id_hash: String,
     pub table_type: String,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for CurrentTokenOwnership {
+    fn txn_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {})",
+            self.token_data_id_hash, self.property_version, self.owner_address
+        )
+    }
+}
+
+/// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
+#[derive(Debug, Identifiable, Queryable)]
+#[diesel(primary_key(token_data_id_hash, property_version, owner_address))]
+#[diesel(table_name = current_token_ownerships)]
+pub struct CurrentTokenOwnershipQuery {
+    pub token_data_id_hash: String,
+    pub property_version: BigDecimal,
+    pub owner_address: String,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub name: String,
+    pub amount: BigDecimal,
+    pub token_properties: serde_json::Value,
+    pub last_transaction_version: i64,
+    pub inserted_at: chrono::NaiveDateTime,
+    pub collection_data_id_hash: String,
+    pub table_type: String,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
 }
 
 impl TokenOwnership {
@@ -108,6 +161,7 @@ impl TokenOwnership {
                     last_transaction_version: txn_version,
                     table_type: tm.table_type.clone(),
                     last_transaction_timestamp: token.transaction_timestamp,
+                    names_encoded: token.names_encoded,
                 }),
                 Some(standardize_address(&tm.owner_address)),
                 Some(tm.table_type.clone()),
@@ -137,20 +191,63 @@ impl TokenOwnership {
                 transaction_version: token.transaction_version,
                 table_handle,
                 transaction_timestamp: token.transaction_timestamp,
+                names_encoded: token.names_encoded,
             },
             curr_token_ownership,
         )))
     }
 }
 
+/// Lookups of tokens currently held, i.e. with a non zero amount, most recently changed first.
+/// Each one is backed by an index on its filter columns followed by `last_transaction_version`.
 impl CurrentTokenOwnership {
-    pub fn get_by_owner(
+    /// Uses `curr_to_oa_ltv_index`
+    pub fn by_owner(
         conn: &mut PgPoolConnection,
-        owner_address: String,
-    ) -> diesel::QueryResult<Option<Self>> {
+        owner_address: &str,
+        limit: i64,
+        offset: i64,
+    ) -> diesel::QueryResult<Vec<CurrentTokenOwnershipQuery>> {
         current_token_ownerships::table
             .filter(current_token_ownerships::owner_address.eq(owner_address))
-            .first::<Self>(conn)
-            .optional()
+            .filter(current_token_ownerships::amount.gt(BigDecimal::zero()))
+            .order(current_token_ownerships::last_transaction_version.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<CurrentTokenOwnershipQuery>(conn)
+    }
+
+    /// Uses `curr_to_cdih_ltv_index`
+    pub fn by_collection(
+        conn: &mut PgPoolConnection,
+        collection_data_id_hash: &str,
+        limit: i64,
+        offset: i64,
+    ) -> diesel::QueryResult<Vec<CurrentTokenOwnershipQuery>> {
+        current_token_ownerships::table
+            .filter(current_token_ownerships::collection_data_id_hash.eq(collection_data_id_hash))
+            .filter(current_token_ownerships::amount.gt(BigDecimal::zero()))
+            .order(current_token_ownerships::last_transaction_version.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<CurrentTokenOwnershipQuery>(conn)
+    }
+
+    /// Uses `curr_to_crea_cn_ltv_index`
+    pub fn by_creator_and_collection(
+        conn: &mut PgPoolConnection,
+        creator_address: &str,
+        collection_name: &str,
+        limit: i64,
+        offset: i64,
+    ) -> diesel::QueryResult<Vec<CurrentTokenOwnershipQuery>> {
+        current_token_ownerships::table
+            .filter(current_token_ownerships::creator_address.eq(creator_address))
+            .filter(current_token_ownerships::collection_name.eq(collection_name))
+            .filter(current_token_ownerships::amount.gt(BigDecimal::zero()))
+            .order(current_token_ownerships::last_transaction_version.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<CurrentTokenOwnershipQuery>(conn)
     }
 }
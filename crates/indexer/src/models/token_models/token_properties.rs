@@ -0,0 +1,215 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use super::{token_datas::TokenData, tokens::Token};
+use crate::{
+    database::LoggableRow,
+    schema::{token_data_properties, token_properties},
+};
+use aptos_types::account_address::AccountAddress;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One decoded entry of a token's property map, so tokens can be filtered by trait
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(
+    token_data_id_hash,
+    property_version,
+    transaction_version,
+    property_key
+))]
+#[diesel(table_name = token_properties)]
+pub struct TokenProperty {
+    pub token_data_id_hash: String,
+    pub property_version: BigDecimal,
+    pub transaction_version: i64,
+    pub property_key: String,
+    pub property_type: String,
+    pub property_value: String,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl LoggableRow for TokenProperty {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {}, {})",
+            self.token_data_id_hash,
+            self.property_version,
+            self.transaction_version,
+            self.property_key
+        )
+    }
+}
+
+impl TokenProperty {
+    pub fn from_token(token: &Token) -> Vec<Self> {
+        decode_property_map(&token.token_properties)
+            .into_iter()
+            .map(|(key, typ, value)| Self {
+                token_data_id_hash: token.token_data_id_hash.clone(),
+                property_version: token.property_version.clone(),
+                transaction_version: token.transaction_version,
+                property_key: key,
+                property_type: typ,
+                property_value: value,
+                transaction_timestamp: token.transaction_timestamp,
+            })
+            .collect()
+    }
+}
+
+/// One decoded entry of a token data's default property map. Every token of the token data starts out
+/// with these, so they're keyed by token data rather than by token.
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(token_data_id_hash, transaction_version, property_key))]
+#[diesel(table_name = token_data_properties)]
+pub struct TokenDataProperty {
+    pub token_data_id_hash: String,
+    pub transaction_version: i64,
+    pub property_key: String,
+    pub property_type: String,
+    pub property_value: String,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl LoggableRow for TokenDataProperty {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {})",
+            self.token_data_id_hash, self.transaction_version, self.property_key
+        )
+    }
+}
+
+impl TokenDataProperty {
+    pub fn from_token_data(token_data: &TokenData) -> Vec<Self> {
+        decode_property_map(&token_data.default_properties)
+            .into_iter()
+            .map(|(key, typ, value)| Self {
+                token_data_id_hash: token_data.token_data_id_hash.clone(),
+                transaction_version: token_data.transaction_version,
+                property_key: key,
+                property_type: typ,
+                property_value: value,
+                transaction_timestamp: token_data.transaction_timestamp,
+            })
+            .collect()
+    }
+}
+
+/// Decodes a property map into (key, type, value) triples. The v1 `0x3::property_map::PropertyMap` is
+/// surfaced as `{"map": {"data": [...]}}`, while a bare `SimpleMap` is surfaced as `{"data": [...]}`.
+/// Entries that aren't shaped like a property are skipped.
+pub fn decode_property_map(property_map: &Value) -> Vec<(String, String, String)> {
+    let entries = property_map
+        .get("map")
+        .unwrap_or(property_map)
+        .get("data")
+        .and_then(Value::as_array);
+    let entries = match entries {
+        Some(entries) => entries,
+        None => return vec![],
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let key = entry.get("key")?.as_str()?;
+            let value = entry.get("value")?;
+            let typ = value.get("type")?.as_str()?;
+            let raw_value = value.get("value")?.as_str()?;
+            Some((
+                key.to_string(),
+                typ.to_string(),
+                decode_property_value(typ, raw_value),
+            ))
+        })
+        .collect()
+}
+
+/// Property values are BCS bytes in hex. Types we know are decoded into a readable string, anything
+/// else (or anything that fails to decode) is kept as the original hex.
+fn decode_property_value(typ: &str, raw_value: &str) -> String {
+    let bytes = match hex::decode(raw_value.trim_start_matches("0x")) {
+        Ok(bytes) => bytes,
+        Err(_) => return raw_value.to_string(),
+    };
+    let decoded = match typ {
+        "bool" => bcs::from_bytes::<bool>(&bytes).map(|v| v.to_string()),
+        "u8" => bcs::from_bytes::<u8>(&bytes).map(|v| v.to_string()),
+        "u64" => bcs::from_bytes::<u64>(&bytes).map(|v| v.to_string()),
+        "u128" => bcs::from_bytes::<u128>(&bytes).map(|v| v.to_string()),
+        "address" => bcs::from_bytes::<AccountAddress>(&bytes).map(|v| v.to_hex_literal()),
+        "0x1::string::String" | "string" => bcs::from_bytes::<String>(&bytes),
+        _ => return raw_value.to_string(),
+    };
+    decoded.unwrap_or_else(|_| raw_value.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(key: &str, typ: &str, value: &[u8]) -> Value {
+        json!({"key": key, "value": {"type": typ, "value": format!("0x{}", hex::encode(value))}})
+    }
+
+    #[test]
+    fn test_decode_v1_property_map() {
+        let property_map = json!({"map": {"data": [
+            entry("rarity", "0x1::string::String", &bcs::to_bytes("legendary").unwrap()),
+            entry("level", "u64", &bcs::to_bytes(&7u64).unwrap()),
+            entry("burnable", "bool", &bcs::to_bytes(&true).unwrap()),
+        ]}});
+        assert_eq!(
+            decode_property_map(&property_map),
+            vec![
+                (
+                    "rarity".to_string(),
+                    "0x1::string::String".to_string(),
+                    "legendary".to_string()
+                ),
+                ("level".to_string(), "u64".to_string(), "7".to_string()),
+                (
+                    "burnable".to_string(),
+                    "bool".to_string(),
+                    "true".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_simple_map_and_unknown_types() {
+        let property_map = json!({"data": [
+            entry("blob", "vector<u8>", &[1, 2, 3]),
+            entry("bad", "u64", &[1]),
+        ]});
+        assert_eq!(
+            decode_property_map(&property_map),
+            vec![
+                (
+                    "blob".to_string(),
+                    "vector<u8>".to_string(),
+                    "0x010203".to_string()
+                ),
+                ("bad".to_string(), "u64".to_string(), "0x01".to_string()),
+            ]
+        );
+        assert!(decode_property_map(&Value::Null).is_empty());
+    }
+}
@@ -4,7 +4,10 @@
 // This is required because a diesel macro makes clippy sad
 #![allow(clippy::extra_unused_lifetimes)]
 
-use crate::util::{hash_str, truncate_str};
+use crate::{
+    counters::SANITIZED_NAMES,
+    util::{self, hash_str, truncate_str},
+};
 use anyhow::{Context, Result};
 use aptos_api_types::deserialize_from_string;
 use bigdecimal::BigDecimal;
@@ -13,6 +16,21 @@ use std::fmt::{self, Formatter};
 
 const NAME_LENGTH: usize = 128;
 const URI_LENGTH: usize = 512;
+
+/// Escapes and truncates collection and token names, counting the ones that needed escaping. Ids are
+/// hashed from the raw names, not these.
+fn sanitize_name_trunc(name: &str) -> String {
+    let (name, escaped) = util::sanitize_name_trunc(name, NAME_LENGTH);
+    if escaped {
+        SANITIZED_NAMES.with_label_values(&["token"]).inc();
+    }
+    name
+}
+
+/// Whether `sanitize_name_trunc` escapes this name, i.e. whether the stored name has to be unescaped
+fn is_name_encoded(name: &str) -> bool {
+    util::sanitize_name_trunc(name, NAME_LENGTH).1
+}
 /**
  * This file defines deserialized move types as defined in our 0x3 contracts.
  */
@@ -35,11 +53,16 @@ impl TokenDataIdType {
     }
 
     pub fn get_collection_trunc(&self) -> String {
-        truncate_str(&self.collection, NAME_LENGTH)
+        sanitize_name_trunc(&self.collection)
     }
 
     pub fn get_name_trunc(&self) -> String {
-        truncate_str(&self.name, NAME_LENGTH)
+        sanitize_name_trunc(&self.name)
+    }
+
+    /// Whether either the collection or the token name is stored escaped
+    pub fn names_encoded(&self) -> bool {
+        is_name_encoded(&self.collection) || is_name_encoded(&self.name)
     }
 
     pub fn get_collection_data_id_hash(&self) -> String {
@@ -68,7 +91,7 @@ impl CollectionDataIdType {
     }
 
     pub fn get_name_trunc(&self) -> String {
-        truncate_str(&self.name, NAME_LENGTH)
+        sanitize_name_trunc(&self.name)
     }
 }
 
@@ -114,7 +137,7 @@ impl TokenDataType {
     }
 
     pub fn get_name_trunc(&self) -> String {
-        truncate_str(&self.name, NAME_LENGTH)
+        sanitize_name_trunc(&self.name)
     }
 }
 
@@ -167,7 +190,11 @@ impl CollectionDataType {
     }
 
     pub fn get_name_trunc(&self) -> String {
-        truncate_str(&self.name, NAME_LENGTH)
+        sanitize_name_trunc(&self.name)
+    }
+
+    pub fn names_encoded(&self) -> bool {
+        is_name_encoded(&self.name)
     }
 }
 
@@ -413,3 +440,73 @@ impl TokenResource {
         ))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::Value;
+
+    const TABLE_ITEM_TYPES: &[&str] = &[
+        "0x3::token::TokenDataId",
+        "0x3::token::TokenId",
+        "0x3::token::TokenData",
+        "0x3::token::Token",
+        "0x3::token::CollectionData",
+        "0x3::token_transfers::TokenOfferId",
+        "0x1::coin::CoinStore",
+    ];
+
+    /// Mostly field names the token types expect, so generated objects get past the first missing field
+    fn arb_key() -> impl Strategy<Value = String> {
+        prop_oneof![
+            prop::sample::select(vec![
+                "creator",
+                "collection",
+                "name",
+                "property_version",
+                "token_data_id",
+                "id",
+                "amount",
+                "supply",
+                "maximum",
+                "largest_property_version",
+                "royalty_points_numerator",
+                "royalty_points_denominator",
+                "to_addr",
+                "token_id",
+            ])
+            .prop_map(str::to_string),
+            ".{0,8}",
+        ]
+    }
+
+    fn arb_json() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Bool),
+            any::<i64>().prop_map(Value::from),
+            any::<f64>().prop_map(Value::from),
+            "[0-9]{0,40}".prop_map(Value::String),
+            ".{0,16}".prop_map(Value::String),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+                prop::collection::vec((arb_key(), inner), 0..8)
+                    .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_from_table_item_type_never_panics(
+            data_type in prop::sample::select(TABLE_ITEM_TYPES),
+            data in arb_json(),
+        ) {
+            // Any of Ok(Some), Ok(None) or Err is fine, as long as it returns
+            let _ = TokenWriteSet::from_table_item_type(data_type, &data, 0);
+        }
+    }
+}
@@ -13,7 +13,7 @@ use super::{
     token_utils::{TokenResource, TokenWriteSet},
 };
 use crate::{
-    database::PgPoolConnection,
+    database::{LoggableRow, PgPoolConnection},
     models::move_resources::MoveResource,
     schema::tokens,
     util::{ensure_not_negative, parse_timestamp, standardize_address},
@@ -53,6 +53,20 @@ pub struct Token {
     pub token_properties: serde_json::Value,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    pub names_encoded: bool,
+}
+
+impl LoggableRow for Token {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!(
+            "({}, {}, {})",
+            self.token_data_id_hash, self.property_version, self.transaction_version
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -71,7 +85,7 @@ impl Token {
         transaction: &APITransaction,
         table_handle_to_owner: &TableHandleToOwner,
         conn: &mut PgPoolConnection,
-    ) -> (
+    ) -> anyhow::Result<(
         Vec<Self>,
         Vec<TokenOwnership>,
         Vec<TokenData>,
@@ -80,7 +94,7 @@ impl Token {
         HashMap<TokenDataIdHash, CurrentTokenData>,
         HashMap<TokenDataIdHash, CurrentCollectionData>,
         HashMap<CurrentTokenPendingClaimPK, CurrentTokenPendingClaim>,
-    ) {
+    )> {
         if let APITransaction::UserTransaction(user_txn) = transaction {
             let mut token_ownerships = vec![];
             let mut token_datas = vec![];
@@ -112,22 +126,19 @@ impl Token {
                             txn_version,
                             txn_timestamp,
                             table_handle_to_owner,
-                        )
-                        .unwrap(),
+                        )?,
                         TokenData::from_write_table_item(
                             write_table_item,
                             txn_version,
                             txn_timestamp,
-                        )
-                        .unwrap(),
+                        )?,
                         CollectionData::from_write_table_item(
                             write_table_item,
                             txn_version,
                             txn_timestamp,
                             table_handle_to_owner,
                             conn,
-                        )
-                        .unwrap(),
+                        )?,
                     ),
                     APIWriteSetChange::DeleteTableItem(delete_table_item) => (
                         Self::from_delete_table_item(
@@ -135,8 +146,7 @@ impl Token {
                             txn_version,
                             txn_timestamp,
                             table_handle_to_owner,
-                        )
-                        .unwrap(),
+                        )?,
                         None,
                         None,
                     ),
@@ -150,8 +160,7 @@ impl Token {
                             txn_version,
                             txn_timestamp,
                             table_handle_to_owner,
-                        )
-                        .unwrap()
+                        )?
                     }
                     APIWriteSetChange::DeleteTableItem(delete_table_item) => {
                         CurrentTokenPendingClaim::from_delete_table_item(
@@ -159,8 +168,7 @@ impl Token {
                             txn_version,
                             txn_timestamp,
                             table_handle_to_owner,
-                        )
-                        .unwrap()
+                        )?
                     }
                     _ => None,
                 };
@@ -215,7 +223,7 @@ impl Token {
                     );
                 }
             }
-            return (
+            return Ok((
                 tokens.into_values().collect(),
                 token_ownerships,
                 token_datas,
@@ -224,9 +232,9 @@ impl Token {
                 current_token_datas,
                 current_collection_datas,
                 current_token_claims,
-            );
+            ));
         }
-        Default::default()
+        Ok(Default::default())
     }
 
     /// Get token from write table item. Table items don't have address of the table so we need to look it up in the table_handle_to_owner mapping
@@ -256,6 +264,7 @@ impl Token {
             let token_data_id_hash = token_data_id.to_hash();
             let collection_name = token_data_id.get_collection_trunc();
             let name = token_data_id.get_name_trunc();
+            let names_encoded = token_data_id.names_encoded();
 
             let token_pg = Self {
                 collection_data_id_hash,
@@ -267,6 +276,7 @@ impl Token {
                 transaction_version: txn_version,
                 token_properties: token.token_properties,
                 transaction_timestamp: txn_timestamp,
+                names_encoded,
             };
 
             let (token_ownership, current_token_ownership) = TokenOwnership::from_token(
@@ -313,6 +323,7 @@ impl Token {
             let token_data_id_hash = token_data_id.to_hash();
             let collection_name = token_data_id.get_collection_trunc();
             let name = token_data_id.get_name_trunc();
+            let names_encoded = token_data_id.names_encoded();
 
             let token = Self {
                 collection_data_id_hash,
@@ -324,6 +335,7 @@ impl Token {
                 transaction_version: txn_version,
                 token_properties: serde_json::Value::Null,
                 transaction_timestamp: txn_timestamp,
+                names_encoded,
             };
             let (token_ownership, current_token_ownership) = TokenOwnership::from_token(
                 &token,
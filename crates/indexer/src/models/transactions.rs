@@ -12,7 +12,7 @@ use crate::{
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::database::PgPoolConnection;
+use crate::database::{LoggableRow, PgPoolConnection};
 use aptos_api_types::{Transaction as APITransaction, TransactionInfo};
 use bigdecimal::BigDecimal;
 use diesel::{
@@ -48,6 +48,16 @@ pub struct Transaction {
     pub epoch: i64,
 }
 
+impl LoggableRow for Transaction {
+    fn txn_version(&self) -> i64 {
+        self.version
+    }
+
+    fn pk(&self) -> String {
+        self.version.to_string()
+    }
+}
+
 /// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
 #[derive(Debug, Deserialize, Identifiable, Queryable, Serialize)]
 #[diesel(primary_key(version))]
@@ -406,11 +416,24 @@ impl TransactionQuery {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum TransactionDetail {
     User(UserTransaction, Vec<Signature>),
     BlockMetadata(BlockMetadataTransaction),
 }
 
+impl LoggableRow for TransactionDetail {
+    fn txn_version(&self) -> i64 {
+        match self {
+            TransactionDetail::User(user_txn, _) => user_txn.version,
+            TransactionDetail::BlockMetadata(block_metadata_txn) => block_metadata_txn.version,
+        }
+    }
+
+    fn pk(&self) -> String {
+        self.txn_version().to_string()
+    }
+}
+
 // Prevent conflicts with other things named `Transaction`
 pub type TransactionModel = Transaction;
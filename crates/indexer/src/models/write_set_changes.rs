@@ -8,7 +8,8 @@ use super::{
     transactions::TransactionQuery,
 };
 use crate::{
-    models::transactions::Transaction, schema::write_set_changes, util::standardize_address,
+    database::LoggableRow, models::transactions::Transaction, schema::write_set_changes,
+    util::standardize_address,
 };
 use aptos_api_types::WriteSetChange as APIWriteSetChange;
 use field_count::FieldCount;
@@ -27,6 +28,16 @@ pub struct WriteSetChange {
     pub address: String,
 }
 
+impl LoggableRow for WriteSetChange {
+    fn txn_version(&self) -> i64 {
+        self.transaction_version
+    }
+
+    fn pk(&self) -> String {
+        format!("({}, {})", self.transaction_version, self.index)
+    }
+}
+
 /// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
 #[derive(Associations, Debug, Deserialize, Identifiable, Queryable, Serialize)]
 #[diesel(belongs_to(TransactionQuery, foreign_key = transaction_version))]
@@ -189,12 +200,31 @@ impl WriteSetChange {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum WriteSetChangeDetail {
     Module(MoveModule),
     Resource(MoveResource),
     Table(TableItem, Option<TableMetadata>),
 }
 
+impl LoggableRow for WriteSetChangeDetail {
+    fn txn_version(&self) -> i64 {
+        match self {
+            WriteSetChangeDetail::Module(module) => module.transaction_version,
+            WriteSetChangeDetail::Resource(resource) => resource.transaction_version,
+            WriteSetChangeDetail::Table(item, _) => item.transaction_version,
+        }
+    }
+
+    fn pk(&self) -> String {
+        let write_set_change_index = match self {
+            WriteSetChangeDetail::Module(module) => module.write_set_change_index,
+            WriteSetChangeDetail::Resource(resource) => resource.write_set_change_index,
+            WriteSetChangeDetail::Table(item, _) => item.write_set_change_index,
+        };
+        format!("({}, {})", self.txn_version(), write_set_change_index)
+    }
+}
+
 // Prevent conflicts with other things named `WriteSetChange`
 pub type WriteSetChangeModel = WriteSetChange;
@@ -3,7 +3,8 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, get_chunks, log_cleaned_rows, log_dry_run,
+        retry_transient_errors, PgDbPool, PgPoolConnection, MAX_DIESEL_PARAM_SIZE,
     },
     indexer::{
         errors::TransactionProcessingError, processing_result::ProcessingResult,
@@ -62,7 +63,7 @@ fn insert_to_db_impl(
     Ok(())
 }
 
-fn insert_to_db(
+async fn insert_to_db(
     conn: &mut PgPoolConnection,
     name: &'static str,
     start_version: u64,
@@ -79,28 +80,44 @@ fn insert_to_db(
         end_version = end_version,
         "Inserting to db",
     );
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
-            insert_to_db_impl(
-                pg_conn,
-                &coin_activities,
-                &coin_infos,
-                &coin_balances,
-                &current_coin_balances,
-                &coin_supply,
-            )
-        }) {
+    match retry_transient_errors(name, || {
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                insert_to_db_impl(
+                    pg_conn,
+                    &coin_activities,
+                    &coin_infos,
+                    &coin_balances,
+                    &current_coin_balances,
+                    &coin_supply,
+                )
+            })
+    })
+    .await
+    {
         Ok(_) => Ok(()),
         Err(_) => conn
             .build_transaction()
             .read_write()
             .run::<_, Error, _>(|pg_conn| {
-                let coin_activities = clean_data_for_db(coin_activities, true);
-                let coin_infos = clean_data_for_db(coin_infos, true);
-                let coin_balances = clean_data_for_db(coin_balances, true);
-                let current_coin_balances = clean_data_for_db(current_coin_balances, true);
+                let (coin_activities, coin_activities_cleaned) =
+                    clean_data_for_db(coin_activities, true);
+                let (coin_infos, coin_infos_cleaned) = clean_data_for_db(coin_infos, true);
+                let (coin_balances, coin_balances_cleaned) = clean_data_for_db(coin_balances, true);
+                let (current_coin_balances, current_coin_balances_cleaned) =
+                    clean_data_for_db(current_coin_balances, true);
+                log_cleaned_rows(
+                    name,
+                    start_version,
+                    end_version,
+                    &[
+                        ("coin_activities", &coin_activities_cleaned),
+                        ("coin_infos", &coin_infos_cleaned),
+                        ("coin_balances", &coin_balances_cleaned),
+                        ("current_coin_balances", &current_coin_balances_cleaned),
+                    ],
+                );
 
                 insert_to_db_impl(
                     pg_conn,
@@ -120,7 +137,11 @@ fn insert_coin_activities(
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_activities::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinActivity::field_count());
+    let chunks = get_chunks(
+        item_to_insert.len(),
+        CoinActivity::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -145,7 +166,11 @@ fn insert_coin_infos(
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_infos::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinInfo::field_count());
+    let chunks = get_chunks(
+        item_to_insert.len(),
+        CoinInfo::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -176,7 +201,11 @@ fn insert_coin_balances(
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_balances::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinBalance::field_count());
+    let chunks = get_chunks(
+        item_to_insert.len(),
+        CoinBalance::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -196,7 +225,11 @@ fn insert_current_coin_balances(
 ) -> Result<(), diesel::result::Error> {
     use schema::current_coin_balances::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CurrentCoinBalance::field_count());
+    let chunks = get_chunks(
+        item_to_insert.len(),
+        CurrentCoinBalance::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -222,7 +255,11 @@ fn insert_coin_supply(
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_supply::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinSupply::field_count());
+    let chunks = get_chunks(
+        item_to_insert.len(),
+        CoinSupply::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -247,12 +284,22 @@ impl TransactionProcessor for CoinTransactionProcessor {
         transactions: Vec<APITransaction>,
         start_version: u64,
         end_version: u64,
+        dry_run: bool,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
         let mut conn = self.get_conn();
         // get aptos_coin info for supply tracking
         // TODO: This only needs to be fetched once. Need to persist somehow
         let maybe_aptos_coin_info =
-            &CoinInfoQuery::get_by_coin_type(APTOS_COIN_TYPE.to_string(), &mut conn).unwrap();
+            &CoinInfoQuery::get_by_coin_type(APTOS_COIN_TYPE.to_string(), &mut conn).map_err(
+                |err| {
+                    TransactionProcessingError::DatabaseError((
+                        anyhow::Error::from(err),
+                        start_version,
+                        end_version,
+                        self.name(),
+                    ))
+                },
+            )?;
 
         let mut all_coin_activities = vec![];
         let mut all_coin_balances = vec![];
@@ -268,7 +315,15 @@ impl TransactionProcessor for CoinTransactionProcessor {
                 coin_infos,
                 current_coin_balances,
                 mut coin_supply,
-            ) = CoinActivity::from_transaction(txn, maybe_aptos_coin_info);
+            ) = CoinActivity::from_transaction(txn, maybe_aptos_coin_info).map_err(|err| {
+                TransactionProcessingError::from_processing_error(
+                    err,
+                    txn.version().unwrap(),
+                    start_version,
+                    end_version,
+                    self.name(),
+                )
+            })?;
             all_coin_activities.append(&mut coin_activities);
             all_coin_balances.append(&mut coin_balances);
             all_coin_supply.append(&mut coin_supply);
@@ -289,24 +344,47 @@ impl TransactionProcessor for CoinTransactionProcessor {
             (&a.owner_address, &a.coin_type).cmp(&(&b.owner_address, &b.coin_type))
         });
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            all_coin_activities,
-            all_coin_infos,
-            all_coin_balances,
-            all_current_coin_balances,
-            all_coin_supply,
-        );
+        let entity_counts = HashMap::from([
+            ("coin_activities", all_coin_activities.len()),
+            ("coin_infos", all_coin_infos.len()),
+            ("coin_balances", all_coin_balances.len()),
+            ("current_coin_balances", all_current_coin_balances.len()),
+            ("coin_supply", all_coin_supply.len()),
+        ]);
+
+        let tx_result = if dry_run {
+            log_dry_run(self.name(), "coin_activities", &all_coin_activities);
+            log_dry_run(self.name(), "coin_infos", &all_coin_infos);
+            log_dry_run(self.name(), "coin_balances", &all_coin_balances);
+            log_dry_run(
+                self.name(),
+                "current_coin_balances",
+                &all_current_coin_balances,
+            );
+            log_dry_run(self.name(), "coin_supply", &all_coin_supply);
+            Ok(())
+        } else {
+            insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                all_coin_activities,
+                all_coin_infos,
+                all_coin_balances,
+                all_current_coin_balances,
+                all_coin_supply,
+            )
+            .await
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
                 start_version,
                 end_version,
+                entity_counts,
             )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+            Err(err) => Err(TransactionProcessingError::DatabaseError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
@@ -3,7 +3,8 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, get_chunks, log_cleaned_rows, log_dry_run,
+        retry_transient_errors, PgDbPool, PgPoolConnection, MAX_DIESEL_PARAM_SIZE,
     },
     indexer::{
         errors::TransactionProcessingError, processing_result::ProcessingResult,
@@ -26,7 +27,7 @@ use aptos_api_types::Transaction;
 use async_trait::async_trait;
 use diesel::{result::Error, PgConnection};
 use field_count::FieldCount;
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
 pub const NAME: &str = "default_processor";
 pub struct DefaultTransactionProcessor {
@@ -50,7 +51,7 @@ impl Debug for DefaultTransactionProcessor {
     }
 }
 
-fn insert_to_db(
+async fn insert_to_db(
     conn: &mut PgPoolConnection,
     name: &'static str,
     start_version: u64,
@@ -67,30 +68,45 @@ fn insert_to_db(
         end_version = end_version,
         "Inserting to db",
     );
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
-            insert_transactions(pg_conn, &txns)?;
-            insert_user_transactions_w_sigs(pg_conn, &txn_details)?;
-            insert_block_metadata_transactions(pg_conn, &txn_details)?;
-            insert_events(pg_conn, &events)?;
-            insert_write_set_changes(pg_conn, &wscs)?;
-            insert_move_modules(pg_conn, &wsc_details)?;
-            insert_move_resources(pg_conn, &wsc_details)?;
-            insert_table_data(pg_conn, &wsc_details)?;
-            Ok(())
-        }) {
+    match retry_transient_errors(name, || {
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                insert_transactions(pg_conn, &txns)?;
+                insert_user_transactions_w_sigs(pg_conn, &txn_details)?;
+                insert_block_metadata_transactions(pg_conn, &txn_details)?;
+                insert_events(pg_conn, &events)?;
+                insert_write_set_changes(pg_conn, &wscs)?;
+                insert_move_modules(pg_conn, &wsc_details)?;
+                insert_move_resources(pg_conn, &wsc_details)?;
+                insert_table_data(pg_conn, &wsc_details)?;
+                Ok(())
+            })
+    })
+    .await
+    {
         Ok(_) => Ok(()),
         Err(_) => conn
             .build_transaction()
             .read_write()
             .run::<_, Error, _>(|pg_conn| {
-                let txns = clean_data_for_db(txns, true);
-                let txn_details = clean_data_for_db(txn_details, true);
-                let events = clean_data_for_db(events, true);
-                let wscs = clean_data_for_db(wscs, true);
-                let wsc_details = clean_data_for_db(wsc_details, true);
+                let (txns, txns_cleaned) = clean_data_for_db(txns, true);
+                let (txn_details, txn_details_cleaned) = clean_data_for_db(txn_details, true);
+                let (events, events_cleaned) = clean_data_for_db(events, true);
+                let (wscs, wscs_cleaned) = clean_data_for_db(wscs, true);
+                let (wsc_details, wsc_details_cleaned) = clean_data_for_db(wsc_details, true);
+                log_cleaned_rows(
+                    name,
+                    start_version,
+                    end_version,
+                    &[
+                        ("transactions", &txns_cleaned),
+                        ("transaction_details", &txn_details_cleaned),
+                        ("events", &events_cleaned),
+                        ("write_set_changes", &wscs_cleaned),
+                        ("write_set_change_details", &wsc_details_cleaned),
+                    ],
+                );
 
                 insert_transactions(pg_conn, &txns)?;
                 insert_user_transactions_w_sigs(pg_conn, &txn_details)?;
@@ -110,7 +126,11 @@ fn insert_transactions(
     txns: &[TransactionModel],
 ) -> Result<(), diesel::result::Error> {
     use schema::transactions::dsl::*;
-    let chunks = get_chunks(txns.len(), TransactionModel::field_count());
+    let chunks = get_chunks(
+        txns.len(),
+        TransactionModel::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -140,6 +160,7 @@ fn insert_user_transactions_w_sigs(
     let chunks = get_chunks(
         all_user_transactions.len(),
         UserTransactionModel::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
     );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -151,7 +172,11 @@ fn insert_user_transactions_w_sigs(
             None,
         )?;
     }
-    let chunks = get_chunks(all_signatures.len(), Signature::field_count());
+    let chunks = get_chunks(
+        all_signatures.len(),
+        Signature::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -184,7 +209,11 @@ fn insert_block_metadata_transactions(
         })
         .collect::<Vec<BlockMetadataTransactionModel>>();
 
-    let chunks = get_chunks(bmt.len(), BlockMetadataTransactionModel::field_count());
+    let chunks = get_chunks(
+        bmt.len(),
+        BlockMetadataTransactionModel::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -201,7 +230,7 @@ fn insert_block_metadata_transactions(
 fn insert_events(conn: &mut PgConnection, ev: &[EventModel]) -> Result<(), diesel::result::Error> {
     use schema::events::dsl::*;
 
-    let chunks = get_chunks(ev.len(), EventModel::field_count());
+    let chunks = get_chunks(ev.len(), EventModel::field_count(), MAX_DIESEL_PARAM_SIZE);
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -222,7 +251,11 @@ fn insert_write_set_changes(
 ) -> Result<(), diesel::result::Error> {
     use schema::write_set_changes::dsl::*;
 
-    let chunks = get_chunks(wscs.len(), WriteSetChangeModel::field_count());
+    let chunks = get_chunks(
+        wscs.len(),
+        WriteSetChangeModel::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -251,7 +284,11 @@ fn insert_move_modules(
         })
         .collect::<Vec<MoveModule>>();
 
-    let chunks = get_chunks(modules.len(), MoveModule::field_count());
+    let chunks = get_chunks(
+        modules.len(),
+        MoveModule::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -279,7 +316,11 @@ fn insert_move_resources(
         })
         .collect::<Vec<MoveResource>>();
 
-    let chunks = get_chunks(resources.len(), MoveResource::field_count());
+    let chunks = get_chunks(
+        resources.len(),
+        MoveResource::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -318,7 +359,7 @@ fn insert_table_data(
     metadata_nonnull.dedup_by(|a, b| a.handle == b.handle);
     metadata_nonnull.sort_by(|a, b| a.handle.cmp(&b.handle));
 
-    let chunks = get_chunks(items.len(), TableItem::field_count());
+    let chunks = get_chunks(items.len(), TableItem::field_count(), MAX_DIESEL_PARAM_SIZE);
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -329,7 +370,11 @@ fn insert_table_data(
             None,
         )?;
     }
-    let chunks = get_chunks(metadata_nonnull.len(), TableMetadata::field_count());
+    let chunks = get_chunks(
+        metadata_nonnull.len(),
+        TableMetadata::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -354,29 +399,46 @@ impl TransactionProcessor for DefaultTransactionProcessor {
         transactions: Vec<Transaction>,
         start_version: u64,
         end_version: u64,
+        dry_run: bool,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
-        let (txns, user_txns, bm_txns, events, write_set_changes) =
+        let (txns, txn_details, events, wscs, wsc_details) =
             TransactionModel::from_transactions(&transactions);
+        let entity_counts = HashMap::from([
+            ("transactions", txns.len()),
+            ("events", events.len()),
+            ("write_set_changes", wscs.len()),
+        ]);
 
-        let mut conn = self.get_conn();
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            txns,
-            user_txns,
-            bm_txns,
-            events,
-            write_set_changes,
-        );
+        let tx_result = if dry_run {
+            log_dry_run(self.name(), "transactions", &txns);
+            log_dry_run(self.name(), "transaction_details", &txn_details);
+            log_dry_run(self.name(), "events", &events);
+            log_dry_run(self.name(), "write_set_changes", &wscs);
+            log_dry_run(self.name(), "write_set_change_details", &wsc_details);
+            Ok(())
+        } else {
+            let mut conn = self.get_conn();
+            insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                txns,
+                txn_details,
+                events,
+                wscs,
+                wsc_details,
+            )
+            .await
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
                 start_version,
                 end_version,
+                entity_counts,
             )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+            Err(err) => Err(TransactionProcessingError::DatabaseError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
@@ -1,9 +1,18 @@
 use std::fmt::Debug;
 
+use diesel::{
+    pg::upsert::excluded,
+    result::{DatabaseErrorKind, Error},
+    Connection, ExpressionMethods, PgConnection,
+};
 use field_count::FieldCount;
 
 use crate::{
-    database::{execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection},
+    counters::PROCESSOR_SKIPPED_ROWS,
+    database::{
+        execute_with_better_error, get_chunks, retry_transient_errors, PgDbPool, PgPoolConnection,
+        MAX_DIESEL_PARAM_SIZE,
+    },
     models::marketplace_models::{
         bids::MarketplaceBids, collections::MarketplaceCollection, offers::MarketplaceOffer,
         orders::MarketplaceOrder,
@@ -34,16 +43,198 @@ impl Debug for MarketplaceProcessor {
     }
 }
 
-fn insert_collections(
+fn insert_to_db_impl(
+    conn: &mut PgConnection,
+    collections: &[MarketplaceCollection],
+    offers: &[MarketplaceOffer],
+    orders: &[MarketplaceOrder],
+    bids: &[MarketplaceBids],
+) -> Result<(), diesel::result::Error> {
+    insert_collections(conn, collections)?;
+    insert_offers(conn, offers)?;
+    insert_orders(conn, orders)?;
+    insert_bids(conn, bids)?;
+    Ok(())
+}
+
+async fn insert_to_db(
     conn: &mut PgPoolConnection,
+    name: &'static str,
+    start_version: u64,
+    end_version: u64,
+    mut collections: Vec<MarketplaceCollection>,
+    mut offers: Vec<MarketplaceOffer>,
+    mut orders: Vec<MarketplaceOrder>,
+    mut bids: Vec<MarketplaceBids>,
+) -> Result<(), diesel::result::Error> {
+    aptos_logger::trace!(
+        name = name,
+        start_version = start_version,
+        end_version = end_version,
+        "Inserting to db",
+    );
+    sort_by_primary_key(&mut collections, &mut offers, &mut orders, &mut bids);
+    match retry_transient_errors(name, || {
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                insert_to_db_impl(pg_conn, &collections, &offers, &orders, &bids)
+            })
+    })
+    .await
+    {
+        Ok(_) => Ok(()),
+        // Anything other than bad data (e.g. a lost connection) would fail row by row too, so fail the batch
+        Err(err) if !is_row_data_error(&err) => Err(err),
+        Err(err) => {
+            aptos_logger::warn!(
+                name = name,
+                start_version = start_version,
+                end_version = end_version,
+                error = ?err,
+                "Batch insert failed, retrying row by row",
+            );
+            conn.build_transaction()
+                .read_write()
+                .run::<_, Error, _>(|pg_conn| {
+                    insert_rows_individually(
+                        pg_conn,
+                        name,
+                        "marketplace_collections",
+                        &collections,
+                        insert_collections,
+                    )?;
+                    insert_rows_individually(
+                        pg_conn,
+                        name,
+                        "marketplace_offers",
+                        &offers,
+                        insert_offers,
+                    )?;
+                    insert_rows_individually(
+                        pg_conn,
+                        name,
+                        "marketplace_orders",
+                        &orders,
+                        insert_orders,
+                    )?;
+                    insert_rows_individually(pg_conn, name, "marketplace_bids", &bids, insert_bids)
+                })
+        }
+    }
+}
+
+/// Sorts every batch by primary key in order to avoid postgres deadlock since we're doing multi threaded db writes.
+/// Two processor tasks upserting overlapping rows will then always lock them in the same order.
+fn sort_by_primary_key(
+    collections: &mut [MarketplaceCollection],
+    offers: &mut [MarketplaceOffer],
+    orders: &mut [MarketplaceOrder],
+    bids: &mut [MarketplaceBids],
+) {
+    collections.sort_by(|a, b| {
+        a.creator_address
+            .cmp(&b.creator_address)
+            .then(a.collection_name.cmp(&b.collection_name))
+    });
+    offers.sort_by(|a, b| {
+        a.creator_address
+            .cmp(&b.creator_address)
+            .then(a.collection_name.cmp(&b.collection_name))
+            .then(a.token_name.cmp(&b.token_name))
+            .then(a.property_version.cmp(&b.property_version))
+    });
+    orders.sort_by(|a, b| {
+        a.token_name
+            .cmp(&b.token_name)
+            .then(a.property_version.cmp(&b.property_version))
+            .then(a.price.cmp(&b.price))
+            .then(a.quantity.cmp(&b.quantity))
+            .then(a.maker.cmp(&b.maker))
+    });
+    bids.sort_by(|a, b| {
+        a.token_name
+            .cmp(&b.token_name)
+            .then(a.property_version.cmp(&b.property_version))
+            .then(a.price.cmp(&b.price))
+            .then(a.maker.cmp(&b.maker))
+    });
+}
+
+/// Inserts rows one at a time, logging and skipping the ones postgres rejects for their data so a single bad row
+/// doesn't cost us the rest of the batch. Every insert runs in its own savepoint, so a failed row doesn't abort
+/// the surrounding transaction. Any other error is returned, failing the batch so it gets retried.
+fn insert_rows_individually<T: Debug>(
+    conn: &mut PgConnection,
+    name: &'static str,
+    table_name: &'static str,
+    rows: &[T],
+    insert_fn: fn(&mut PgConnection, &[T]) -> Result<(), diesel::result::Error>,
+) -> Result<(), diesel::result::Error> {
+    for row in rows {
+        match conn.transaction(|conn| insert_fn(conn, std::slice::from_ref(row))) {
+            Ok(_) => {}
+            Err(err) if is_row_data_error(&err) => {
+                aptos_logger::error!(
+                    name = name,
+                    table_name = table_name,
+                    row = ?row,
+                    error = ?err,
+                    "Failed to insert row, skipping",
+                );
+                PROCESSOR_SKIPPED_ROWS
+                    .with_label_values(&[name, table_name])
+                    .inc();
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Errors caused by the row itself, which would fail the same way on every retry. Diesel has no error kind for
+/// data exceptions (SQLSTATE class 22) so those are recognized by their message.
+fn is_row_data_error(error: &Error) -> bool {
+    match error {
+        Error::DatabaseError(
+            DatabaseErrorKind::CheckViolation
+            | DatabaseErrorKind::NotNullViolation
+            | DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        ) => true,
+        Error::DatabaseError(DatabaseErrorKind::Unknown, info) => {
+            let message = info.message();
+            message.starts_with("invalid input")
+                || message.starts_with("invalid byte sequence")
+                || message.starts_with("value too long")
+                || message.contains("out of range")
+        }
+        _ => false,
+    }
+}
+
+fn insert_collections(
+    conn: &mut PgConnection,
     collections: &[MarketplaceCollection],
 ) -> Result<(), diesel::result::Error> {
-    let chunks = get_chunks(collections.len(), MarketplaceCollection::field_count());
+    use schema::marketplace_collections::dsl::*;
+
+    let chunks = get_chunks(
+        collections.len(),
+        MarketplaceCollection::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_index, end_index) in chunks {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::marketplace_collections::table)
-                .values(&collections[start_index..end_index]),
+                .values(&collections[start_index..end_index])
+                .on_conflict((creator_address, collection_name))
+                .do_update()
+                .set((
+                    creation_timestamp.eq(excluded(creation_timestamp)),
+                    txn_version.eq(excluded(txn_version)),
+                )),
             None,
         )?;
     }
@@ -51,15 +242,34 @@ fn insert_collections(
 }
 
 fn insert_offers(
-    conn: &mut PgPoolConnection,
+    conn: &mut PgConnection,
     offers: &[MarketplaceOffer],
 ) -> Result<(), diesel::result::Error> {
-    let chunks = get_chunks(offers.len(), MarketplaceOffer::field_count());
+    use schema::marketplace_offers::dsl::*;
+
+    let chunks = get_chunks(
+        offers.len(),
+        MarketplaceOffer::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_index, end_index) in chunks {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::marketplace_offers::table)
-                .values(&offers[start_index..end_index]),
+                .values(&offers[start_index..end_index])
+                .on_conflict((
+                    creator_address,
+                    collection_name,
+                    token_name,
+                    property_version,
+                ))
+                .do_update()
+                .set((
+                    price.eq(excluded(price)),
+                    seller.eq(excluded(seller)),
+                    timestamp.eq(excluded(timestamp)),
+                    txn_version.eq(excluded(txn_version)),
+                )),
             None,
         )?;
     }
@@ -67,15 +277,30 @@ fn insert_offers(
 }
 
 fn insert_orders(
-    conn: &mut PgPoolConnection,
+    conn: &mut PgConnection,
     orders: &[MarketplaceOrder],
 ) -> Result<(), diesel::result::Error> {
-    let chunks = get_chunks(orders.len(), MarketplaceOffer::field_count());
+    use schema::marketplace_orders::dsl::*;
+
+    let chunks = get_chunks(
+        orders.len(),
+        MarketplaceOrder::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_index, end_index) in chunks {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::marketplace_orders::table)
-                .values(&orders[start_index..end_index]),
+                .values(&orders[start_index..end_index])
+                .on_conflict((token_name, property_version, price, quantity, maker))
+                .do_update()
+                .set((
+                    creator_address.eq(excluded(creator_address)),
+                    collection_name.eq(excluded(collection_name)),
+                    timestamp.eq(excluded(timestamp)),
+                    txn_version.eq(excluded(txn_version)),
+                    names_encoded.eq(excluded(names_encoded)),
+                )),
             None,
         )?;
     }
@@ -83,17 +308,406 @@ fn insert_orders(
 }
 
 fn insert_bids(
-    conn: &mut PgPoolConnection,
+    conn: &mut PgConnection,
     bids: &[MarketplaceBids],
 ) -> Result<(), diesel::result::Error> {
-    let chunks = get_chunks(bids.len(), MarketplaceOffer::field_count());
+    use schema::marketplace_bids::dsl::*;
+
+    let chunks = get_chunks(
+        bids.len(),
+        MarketplaceBids::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_index, end_index) in chunks {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::marketplace_bids::table)
-                .values(&bids[start_index..end_index]),
+                .values(&bids[start_index..end_index])
+                .on_conflict((token_name, property_version, price, maker))
+                .do_update()
+                .set((
+                    creator_address.eq(excluded(creator_address)),
+                    collection_name.eq(excluded(collection_name)),
+                    timestamp.eq(excluded(timestamp)),
+                    txn_version.eq(excluded(txn_version)),
+                    names_encoded.eq(excluded(names_encoded)),
+                )),
             None,
         )?;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        load_fixture_transactions, models::marketplace_models::offers::MarketplaceOfferQuery,
+        setup_test_database, TestDatabaseLock,
+    };
+    use aptos_api_types::{Transaction, TransactionPayload, UserTransaction};
+    use diesel::{QueryDsl, RunQueryDsl};
+
+    fn get_test_conn() -> (PgPoolConnection, TestDatabaseLock) {
+        let (conn_pool, db_lock) = setup_test_database();
+        (conn_pool.get().unwrap(), db_lock)
+    }
+
+    fn test_collection() -> MarketplaceCollection {
+        MarketplaceCollection {
+            creator_address: "0x1".to_string(),
+            collection_name: "collection".to_string(),
+            creation_timestamp: chrono::NaiveDateTime::from_timestamp(0, 0),
+            txn_version: 1,
+            names_encoded: false,
+        }
+    }
+
+    fn test_offer(price: i64) -> MarketplaceOffer {
+        MarketplaceOffer {
+            creator_address: "0x1".to_string(),
+            collection_name: "collection".to_string(),
+            token_name: "token".to_string(),
+            property_version: 0,
+            price,
+            seller: "0x2".to_string(),
+            timestamp: chrono::NaiveDateTime::from_timestamp(price, 0),
+            txn_version: 1,
+            names_encoded: false,
+        }
+    }
+
+    fn test_order() -> MarketplaceOrder {
+        MarketplaceOrder {
+            creator_address: "0x1".to_string(),
+            collection_name: "collection".to_string(),
+            token_name: "token".to_string(),
+            property_version: 0,
+            price: 100,
+            quantity: 1,
+            maker: "0x3".to_string(),
+            timestamp: chrono::NaiveDateTime::from_timestamp(0, 0),
+            txn_version: 1,
+            names_encoded: false,
+        }
+    }
+
+    fn test_bid() -> MarketplaceBids {
+        MarketplaceBids {
+            creator_address: "0x1".to_string(),
+            collection_name: "collection".to_string(),
+            token_name: "token".to_string(),
+            property_version: 0,
+            price: 90,
+            maker: "0x4".to_string(),
+            timestamp: chrono::NaiveDateTime::from_timestamp(0, 0),
+            txn_version: 1,
+            names_encoded: false,
+        }
+    }
+
+    fn insert_test_batch(conn: &mut PgPoolConnection) -> Result<(), diesel::result::Error> {
+        insert_collections(conn, &[test_collection()])?;
+        insert_offers(conn, &[test_offer(100)])?;
+        insert_orders(conn, &[test_order()])?;
+        insert_bids(conn, &[test_bid()])
+    }
+
+    fn count_rows(conn: &mut PgPoolConnection) -> Result<[i64; 4], diesel::result::Error> {
+        Ok([
+            schema::marketplace_collections::table
+                .count()
+                .get_result(conn)?,
+            schema::marketplace_offers::table.count().get_result(conn)?,
+            schema::marketplace_orders::table.count().get_result(conn)?,
+            schema::marketplace_bids::table.count().get_result(conn)?,
+        ])
+    }
+
+    #[test]
+    fn test_batches_are_sorted_by_primary_key() {
+        let mut collections = vec![
+            MarketplaceCollection {
+                collection_name: "b".to_string(),
+                ..test_collection()
+            },
+            MarketplaceCollection {
+                collection_name: "a".to_string(),
+                ..test_collection()
+            },
+        ];
+        let mut offers = vec![
+            MarketplaceOffer {
+                property_version: 1,
+                ..test_offer(100)
+            },
+            test_offer(200),
+        ];
+        let mut orders = vec![
+            MarketplaceOrder {
+                maker: "0x5".to_string(),
+                ..test_order()
+            },
+            test_order(),
+        ];
+        let mut bids = vec![
+            test_bid(),
+            MarketplaceBids {
+                price: 80,
+                ..test_bid()
+            },
+        ];
+
+        sort_by_primary_key(&mut collections, &mut offers, &mut orders, &mut bids);
+
+        // Every processor task must lock rows in this order, otherwise concurrent batches can deadlock
+        assert_eq!(collections[0].collection_name, "a");
+        assert_eq!(offers[0].property_version, 0);
+        assert_eq!(orders[0].maker, "0x3");
+        assert_eq!(bids[0].price, 80);
+    }
+
+    #[test]
+    fn test_only_row_data_errors_are_skipped() {
+        let db_error =
+            |kind, message: &str| Error::DatabaseError(kind, Box::new(message.to_string()));
+
+        assert!(is_row_data_error(&db_error(
+            DatabaseErrorKind::ForeignKeyViolation,
+            "insert or update on table \"marketplace_offers\" violates foreign key constraint"
+        )));
+        assert!(is_row_data_error(&db_error(
+            DatabaseErrorKind::Unknown,
+            "invalid byte sequence for encoding \"UTF8\": 0x00"
+        )));
+        assert!(!is_row_data_error(&db_error(
+            DatabaseErrorKind::ClosedConnection,
+            "server closed the connection unexpectedly"
+        )));
+        assert!(!is_row_data_error(&db_error(
+            DatabaseErrorKind::Unknown,
+            "current transaction is aborted, commands ignored until end of transaction block"
+        )));
+        assert!(!is_row_data_error(&Error::BrokenTransactionManager));
+    }
+
+    #[test]
+    fn test_offer_fixture() {
+        let txns = load_fixture_transactions("marketplace_offer.json");
+        let user_txns = txns
+            .iter()
+            .filter_map(|txn| match txn {
+                Transaction::UserTransaction(user_txn) => Some(user_txn.as_ref()),
+                _ => None,
+            })
+            .collect::<Vec<&UserTransaction>>();
+        let offers = user_txns
+            .iter()
+            .filter_map(|user_txn| MarketplaceOffer::from_transaction(user_txn))
+            .collect::<Vec<MarketplaceOffer>>();
+        assert_eq!(offers.len(), 1);
+        assert_eq!(
+            offers[0].creator_address,
+            "0x7a1ef4a4c1a5e0f8d3b5c6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b"
+        );
+        assert_eq!(offers[0].collection_name, "Aptos Monkeys");
+        assert_eq!(offers[0].token_name, "Aptos Monkey #42");
+        assert_eq!(offers[0].property_version, 0);
+        assert_eq!(offers[0].price, 150000000);
+        assert_eq!(offers[0].txn_version, 312056);
+        assert_eq!(
+            offers[0].seller,
+            "0x2a0e66fde889cebf0401e676bb9bfa073e03caa9c009c66b739c30d24dccad81"
+        );
+
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (mut conn, _db_lock) = get_test_conn();
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            // Offers reference their collection, so it has to be there first
+            let collections = user_txns
+                .iter()
+                .filter_map(|user_txn| MarketplaceCollection::from_transaction(user_txn))
+                .collect::<Vec<MarketplaceCollection>>();
+            insert_collections(conn, &collections)?;
+            insert_offers(conn, &offers)?;
+            let stored = schema::marketplace_offers::table.load::<MarketplaceOfferQuery>(conn)?;
+            assert_eq!(stored.len(), 1);
+            assert_eq!(stored[0].token_name, offers[0].token_name);
+            assert_eq!(stored[0].price, offers[0].price);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_payload_without_arguments_is_skipped() {
+        let mut txns = load_fixture_transactions("marketplace_offer.json");
+        let user_txn = match &mut txns[0] {
+            Transaction::UserTransaction(user_txn) => user_txn,
+            _ => panic!("Fixture should be a user transaction"),
+        };
+        match &mut user_txn.request.payload {
+            TransactionPayload::EntryFunctionPayload(payload) => payload.arguments.clear(),
+            _ => panic!("Fixture should be an entry function call"),
+        }
+
+        assert!(MarketplaceCollection::from_transaction(user_txn).is_none());
+        assert!(MarketplaceOffer::from_transaction(user_txn).is_none());
+        assert!(MarketplaceOrder::from_transaction(user_txn).is_none());
+        assert!(MarketplaceBids::from_transaction(user_txn).is_none());
+    }
+
+    #[test]
+    fn test_malformed_arguments_are_skipped() {
+        let mut txns = load_fixture_transactions("marketplace_offer.json");
+        let user_txn = match &mut txns[0] {
+            Transaction::UserTransaction(user_txn) => user_txn,
+            _ => panic!("Fixture should be a user transaction"),
+        };
+        fn set_first_argument(user_txn: &mut UserTransaction, argument: serde_json::Value) {
+            match &mut user_txn.request.payload {
+                TransactionPayload::EntryFunctionPayload(payload) => {
+                    payload.arguments[0] = argument
+                }
+                _ => panic!("Fixture should be an entry function call"),
+            }
+        }
+
+        // The API encodes u64s as strings
+        set_first_argument(
+            user_txn,
+            serde_json::json!({
+                "creator": "0x1",
+                "collection_name": "Aptos Monkeys",
+                "token_name": "Aptos Monkey #42",
+                "property_version": "0",
+                "price": "150000000",
+            }),
+        );
+        assert_eq!(
+            MarketplaceOffer::from_transaction(user_txn).unwrap().price,
+            150000000
+        );
+
+        for argument in [
+            serde_json::json!("not an object"),
+            serde_json::json!({ "creator": "0x1", "collection_name": "Aptos Monkeys" }),
+            serde_json::json!({
+                "creator": "0x1",
+                "collection_name": "Aptos Monkeys",
+                "token_name": "Aptos Monkey #42",
+                "property_version": [0],
+                "price": 150000000,
+            }),
+            serde_json::json!({
+                "creator": 1,
+                "collection_name": "Aptos Monkeys",
+                "token_name": "Aptos Monkey #42",
+                "property_version": 0,
+                "price": 150000000,
+            }),
+        ] {
+            set_first_argument(user_txn, argument);
+            assert!(MarketplaceOffer::from_transaction(user_txn).is_none());
+            assert!(MarketplaceOrder::from_transaction(user_txn).is_none());
+            assert!(MarketplaceBids::from_transaction(user_txn).is_none());
+        }
+    }
+
+    #[test]
+    fn test_reprocessing_is_idempotent() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (mut conn, _db_lock) = get_test_conn();
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            insert_test_batch(conn)?;
+            let counts = count_rows(conn)?;
+            assert_eq!(counts, [1, 1, 1, 1]);
+
+            // Processing the same batch again must not add any rows
+            insert_test_batch(conn)?;
+            assert_eq!(count_rows(conn)?, counts);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_relisted_offer_overwrites_price() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (mut conn, _db_lock) = get_test_conn();
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            insert_collections(conn, &[test_collection()])?;
+            insert_offers(conn, &[test_offer(100)])?;
+            insert_offers(conn, &[test_offer(200)])?;
+
+            let offers = schema::marketplace_offers::table.load::<MarketplaceOfferQuery>(conn)?;
+            assert_eq!(offers.len(), 1);
+            assert_eq!(offers[0].price, 200);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_audit_columns_are_set_by_the_database() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (mut conn, _db_lock) = get_test_conn();
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            insert_collections(conn, &[test_collection()])?;
+            insert_offers(conn, &[test_offer(100)])?;
+            insert_offers(conn, &[test_offer(200)])?;
+
+            // The indexer never writes the audit columns, the upsert above only moves updated_at
+            let offers = schema::marketplace_offers::table.load::<MarketplaceOfferQuery>(conn)?;
+            assert!(offers[0].updated_at > offers[0].created_at);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_replaying_a_transaction_upserts() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let txns = load_fixture_transactions("marketplace_offer.json");
+        let user_txn = match &txns[0] {
+            Transaction::UserTransaction(user_txn) => user_txn,
+            _ => panic!("Fixture should be a user transaction"),
+        };
+        let replay = |conn: &mut PgPoolConnection| {
+            futures::executor::block_on(insert_to_db(
+                conn,
+                NAME,
+                312056,
+                312056,
+                MarketplaceCollection::from_transaction(user_txn)
+                    .into_iter()
+                    .collect(),
+                MarketplaceOffer::from_transaction(user_txn)
+                    .into_iter()
+                    .collect(),
+                vec![],
+                vec![],
+            ))
+        };
+        let (mut conn, _db_lock) = get_test_conn();
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            replay(conn)?;
+            let first = schema::marketplace_offers::table.load::<MarketplaceOfferQuery>(conn)?;
+            assert_eq!(first.len(), 1);
+
+            // Same (creator_address, collection_name, token_name, property_version), so this must hit the upsert
+            replay(conn)?;
+            let second = schema::marketplace_offers::table.load::<MarketplaceOfferQuery>(conn)?;
+            assert_eq!(second.len(), 1);
+            assert_eq!(second[0].created_at, first[0].created_at);
+            assert!(second[0].updated_at > first[0].updated_at);
+            Ok(())
+        });
+    }
+}
@@ -10,8 +10,10 @@ pub mod token_processor;
 use self::coin_processor::NAME as COIN_PROCESSOR_NAME;
 use self::default_processor::NAME as DEFAULT_PROCESSOR_NAME;
 use self::marketplace_processor::NAME as MARKETPLACE_PROCESSOR_NAME;
+use self::stake_processor::NAME as STAKE_PROCESSOR_NAME;
 use self::token_processor::NAME as TOKEN_PROCESSOR_NAME;
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Processor {
     CoinProcessor,
     DefaultProcessor,
@@ -21,6 +23,28 @@ pub enum Processor {
 }
 
 impl Processor {
+    /// Every processor the indexer knows how to run.
+    pub fn all() -> &'static [Processor] {
+        &[
+            Self::CoinProcessor,
+            Self::DefaultProcessor,
+            Self::TokenProcessor,
+            Self::StakeProcessor,
+            Self::MarketplaceProcessor,
+        ]
+    }
+
+    /// The name used for this processor in config and in `processor_statuses`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CoinProcessor => COIN_PROCESSOR_NAME,
+            Self::DefaultProcessor => DEFAULT_PROCESSOR_NAME,
+            Self::TokenProcessor => TOKEN_PROCESSOR_NAME,
+            Self::StakeProcessor => STAKE_PROCESSOR_NAME,
+            Self::MarketplaceProcessor => MARKETPLACE_PROCESSOR_NAME,
+        }
+    }
+
     pub fn from_string(input_str: &String) -> Self {
         match input_str.as_str() {
             DEFAULT_PROCESSOR_NAME => Self::DefaultProcessor,
@@ -32,3 +56,18 @@ impl Processor {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_processor_name_round_trip() {
+        for processor in Processor::all() {
+            assert_eq!(
+                Processor::from_string(&processor.name().to_string()),
+                *processor
+            );
+        }
+    }
+}
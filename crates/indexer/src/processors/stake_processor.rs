@@ -3,7 +3,8 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, get_chunks, log_cleaned_rows, log_dry_run,
+        retry_transient_errors, PgDbPool, PgPoolConnection, MAX_DIESEL_PARAM_SIZE,
     },
     indexer::{
         errors::TransactionProcessingError, processing_result::ProcessingResult,
@@ -48,7 +49,7 @@ fn insert_to_db_impl(
     Ok(())
 }
 
-fn insert_to_db(
+async fn insert_to_db(
     conn: &mut PgPoolConnection,
     name: &'static str,
     start_version: u64,
@@ -61,17 +62,29 @@ fn insert_to_db(
         end_version = end_version,
         "Inserting to db",
     );
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| insert_to_db_impl(pg_conn, &current_stake_pool_voters))
+    match retry_transient_errors(name, || {
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| insert_to_db_impl(pg_conn, &current_stake_pool_voters))
+    })
+    .await
     {
         Ok(_) => Ok(()),
         Err(_) => conn
             .build_transaction()
             .read_write()
             .run::<_, Error, _>(|pg_conn| {
-                let current_stake_pool_voters = clean_data_for_db(current_stake_pool_voters, true);
+                let (current_stake_pool_voters, current_stake_pool_voters_cleaned) =
+                    clean_data_for_db(current_stake_pool_voters, true);
+                log_cleaned_rows(
+                    name,
+                    start_version,
+                    end_version,
+                    &[(
+                        "current_staking_pool_voter",
+                        &current_stake_pool_voters_cleaned,
+                    )],
+                );
 
                 insert_to_db_impl(pg_conn, &current_stake_pool_voters)
             }),
@@ -84,7 +97,11 @@ fn insert_current_stake_pool_voter(
 ) -> Result<(), diesel::result::Error> {
     use schema::current_staking_pool_voter::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CurrentStakingPoolVoter::field_count());
+    let chunks = get_chunks(
+        item_to_insert.len(),
+        CurrentStakingPoolVoter::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -117,11 +134,21 @@ impl TransactionProcessor for StakeTransactionProcessor {
         transactions: Vec<APITransaction>,
         start_version: u64,
         end_version: u64,
+        dry_run: bool,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
         let mut all_current_stake_pool_voters: StakingPoolVoterMap = HashMap::new();
 
         for txn in &transactions {
-            let current_stake_pool_voter = CurrentStakingPoolVoter::from_transaction(txn).unwrap();
+            let current_stake_pool_voter =
+                CurrentStakingPoolVoter::from_transaction(txn).map_err(|err| {
+                    TransactionProcessingError::from_processing_error(
+                        err,
+                        txn.version().unwrap(),
+                        start_version,
+                        end_version,
+                        self.name(),
+                    )
+                })?;
             all_current_stake_pool_voters.extend(current_stake_pool_voter);
         }
         let mut all_current_stake_pool_voters = all_current_stake_pool_voters
@@ -131,22 +158,37 @@ impl TransactionProcessor for StakeTransactionProcessor {
         // Sort by PK
         all_current_stake_pool_voters
             .sort_by(|a, b| a.staking_pool_address.cmp(&b.staking_pool_address));
+        let entity_counts = HashMap::from([(
+            "current_staking_pool_voter",
+            all_current_stake_pool_voters.len(),
+        )]);
 
-        let mut conn = self.get_conn();
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            all_current_stake_pool_voters,
-        );
+        let tx_result = if dry_run {
+            log_dry_run(
+                self.name(),
+                "current_staking_pool_voter",
+                &all_current_stake_pool_voters,
+            );
+            Ok(())
+        } else {
+            let mut conn = self.get_conn();
+            insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                all_current_stake_pool_voters,
+            )
+            .await
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
                 start_version,
                 end_version,
+                entity_counts,
             )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+            Err(err) => Err(TransactionProcessingError::DatabaseError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
@@ -3,7 +3,8 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, get_chunks, log_cleaned_rows, log_dry_run,
+        retry_transient_errors, PgDbPool, PgPoolConnection, MAX_DIESEL_PARAM_SIZE,
     },
     indexer::{
         errors::TransactionProcessingError, processing_result::ProcessingResult,
@@ -16,6 +17,7 @@ use crate::{
         token_claims::CurrentTokenPendingClaim,
         token_datas::{CurrentTokenData, TokenData},
         token_ownerships::{CurrentTokenOwnership, TokenOwnership},
+        token_properties::{TokenDataProperty, TokenProperty},
         tokens::{
             CurrentTokenOwnershipPK, CurrentTokenPendingClaimPK, TableMetadataForToken, Token,
             TokenDataIdHash,
@@ -68,6 +70,8 @@ fn insert_to_db_impl(
         &[CurrentCollectionData],
     ),
     token_activities: &[TokenActivity],
+    token_properties: &[TokenProperty],
+    token_data_properties: &[TokenDataProperty],
     current_token_claims: &[CurrentTokenPendingClaim],
     current_ans_lookups: &[CurrentAnsLookup],
 ) -> Result<(), diesel::result::Error> {
@@ -82,12 +86,14 @@ fn insert_to_db_impl(
     insert_current_token_datas(conn, current_token_datas)?;
     insert_current_collection_datas(conn, current_collection_datas)?;
     insert_token_activities(conn, token_activities)?;
+    insert_token_properties(conn, token_properties)?;
+    insert_token_data_properties(conn, token_data_properties)?;
     insert_current_token_claims(conn, current_token_claims)?;
     insert_current_ans_lookups(conn, current_ans_lookups)?;
     Ok(())
 }
 
-fn insert_to_db(
+async fn insert_to_db(
     conn: &mut PgPoolConnection,
     name: &'static str,
     start_version: u64,
@@ -104,6 +110,8 @@ fn insert_to_db(
         Vec<CurrentCollectionData>,
     ),
     token_activities: Vec<TokenActivity>,
+    token_properties: Vec<TokenProperty>,
+    token_data_properties: Vec<TokenDataProperty>,
     current_token_claims: Vec<CurrentTokenPendingClaim>,
     current_ans_lookups: Vec<CurrentAnsLookup>,
 ) -> Result<(), diesel::result::Error> {
@@ -116,38 +124,80 @@ fn insert_to_db(
     let (tokens, token_ownerships, token_datas, collection_datas) = basic_token_transaction_lists;
     let (current_token_ownerships, current_token_datas, current_collection_datas) =
         basic_token_current_lists;
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
-            insert_to_db_impl(
-                pg_conn,
-                (&tokens, &token_ownerships, &token_datas, &collection_datas),
-                (
-                    &current_token_ownerships,
-                    &current_token_datas,
-                    &current_collection_datas,
-                ),
-                &token_activities,
-                &current_token_claims,
-                &current_ans_lookups,
-            )
-        }) {
+    match retry_transient_errors(name, || {
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                insert_to_db_impl(
+                    pg_conn,
+                    (&tokens, &token_ownerships, &token_datas, &collection_datas),
+                    (
+                        &current_token_ownerships,
+                        &current_token_datas,
+                        &current_collection_datas,
+                    ),
+                    &token_activities,
+                    &token_properties,
+                    &token_data_properties,
+                    &current_token_claims,
+                    &current_ans_lookups,
+                )
+            })
+    })
+    .await
+    {
         Ok(_) => Ok(()),
         Err(_) => conn
             .build_transaction()
             .read_write()
             .run::<_, Error, _>(|pg_conn| {
-                let tokens = clean_data_for_db(tokens, true);
-                let token_datas = clean_data_for_db(token_datas, true);
-                let token_ownerships = clean_data_for_db(token_ownerships, true);
-                let collection_datas = clean_data_for_db(collection_datas, true);
-                let current_token_ownerships = clean_data_for_db(current_token_ownerships, true);
-                let current_token_datas = clean_data_for_db(current_token_datas, true);
-                let current_collection_datas = clean_data_for_db(current_collection_datas, true);
-                let token_activities = clean_data_for_db(token_activities, true);
-                let current_token_claims = clean_data_for_db(current_token_claims, true);
-                let current_ans_lookups = clean_data_for_db(current_ans_lookups, true);
+                let (tokens, tokens_cleaned) = clean_data_for_db(tokens, true);
+                let (token_datas, token_datas_cleaned) = clean_data_for_db(token_datas, true);
+                let (token_ownerships, token_ownerships_cleaned) =
+                    clean_data_for_db(token_ownerships, true);
+                let (collection_datas, collection_datas_cleaned) =
+                    clean_data_for_db(collection_datas, true);
+                let (current_token_ownerships, current_token_ownerships_cleaned) =
+                    clean_data_for_db(current_token_ownerships, true);
+                let (current_token_datas, current_token_datas_cleaned) =
+                    clean_data_for_db(current_token_datas, true);
+                let (current_collection_datas, current_collection_datas_cleaned) =
+                    clean_data_for_db(current_collection_datas, true);
+                let (token_activities, token_activities_cleaned) =
+                    clean_data_for_db(token_activities, true);
+                let (token_properties, token_properties_cleaned) =
+                    clean_data_for_db(token_properties, true);
+                let (token_data_properties, token_data_properties_cleaned) =
+                    clean_data_for_db(token_data_properties, true);
+                let (current_token_claims, current_token_claims_cleaned) =
+                    clean_data_for_db(current_token_claims, true);
+                let (current_ans_lookups, current_ans_lookups_cleaned) =
+                    clean_data_for_db(current_ans_lookups, true);
+                log_cleaned_rows(
+                    name,
+                    start_version,
+                    end_version,
+                    &[
+                        ("tokens", &tokens_cleaned),
+                        ("token_datas", &token_datas_cleaned),
+                        ("token_ownerships", &token_ownerships_cleaned),
+                        ("collection_datas", &collection_datas_cleaned),
+                        (
+                            "current_token_ownerships",
+                            &current_token_ownerships_cleaned,
+                        ),
+                        ("current_token_datas", &current_token_datas_cleaned),
+                        (
+                            "current_collection_datas",
+                            &current_collection_datas_cleaned,
+                        ),
+                        ("token_activities", &token_activities_cleaned),
+                        ("token_properties", &token_properties_cleaned),
+                        ("token_data_properties", &token_data_properties_cleaned),
+                        ("current_token_claims", &current_token_claims_cleaned),
+                        ("current_ans_lookups", &current_ans_lookups_cleaned),
+                    ],
+                );
 
                 insert_to_db_impl(
                     pg_conn,
@@ -158,6 +208,8 @@ fn insert_to_db(
                         &current_collection_datas,
                     ),
                     &token_activities,
+                    &token_properties,
+                    &token_data_properties,
                     &current_token_claims,
                     &current_ans_lookups,
                 )
@@ -171,7 +223,11 @@ fn insert_tokens(
 ) -> Result<(), diesel::result::Error> {
     use schema::tokens::dsl::*;
 
-    let chunks = get_chunks(tokens_to_insert.len(), Token::field_count());
+    let chunks = get_chunks(
+        tokens_to_insert.len(),
+        Token::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -198,6 +254,7 @@ fn insert_token_ownerships(
     let chunks = get_chunks(
         token_ownerships_to_insert.len(),
         TokenOwnership::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
     );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -227,7 +284,11 @@ fn insert_token_datas(
 ) -> Result<(), diesel::result::Error> {
     use schema::token_datas::dsl::*;
 
-    let chunks = get_chunks(token_datas_to_insert.len(), TokenData::field_count());
+    let chunks = get_chunks(
+        token_datas_to_insert.len(),
+        TokenData::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
             conn,
@@ -254,6 +315,7 @@ fn insert_collection_datas(
     let chunks = get_chunks(
         collection_datas_to_insert.len(),
         CollectionData::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
     );
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -274,7 +336,11 @@ fn insert_current_token_ownerships(
 ) -> Result<(), diesel::result::Error> {
     use schema::current_token_ownerships::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentTokenOwnership::field_count());
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        CurrentTokenOwnership::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -289,6 +355,7 @@ fn insert_current_token_ownerships(
                     name.eq(excluded(name)),
                     amount.eq(excluded(amount)),
                     token_properties.eq(excluded(token_properties)),
+                    names_encoded.eq(excluded(names_encoded)),
                     last_transaction_version.eq(excluded(last_transaction_version)),
                     collection_data_id_hash.eq(excluded(collection_data_id_hash)),
                     table_type.eq(excluded(table_type)),
@@ -306,7 +373,11 @@ fn insert_current_token_datas(
 ) -> Result<(), diesel::result::Error> {
     use schema::current_token_datas::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentTokenData::field_count());
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        CurrentTokenData::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -320,6 +391,7 @@ fn insert_current_token_datas(
                     collection_name.eq(excluded(collection_name)),
                     name.eq(excluded(name)),
                     maximum.eq(excluded(maximum)),
+                    names_encoded.eq(excluded(names_encoded)),
                     supply.eq(excluded(supply)),
                     largest_property_version.eq(excluded(largest_property_version)),
                     metadata_uri.eq(excluded(metadata_uri)),
@@ -349,7 +421,11 @@ fn insert_current_collection_datas(
 ) -> Result<(), diesel::result::Error> {
     use schema::current_collection_datas::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentCollectionData::field_count());
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        CurrentCollectionData::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -362,6 +438,7 @@ fn insert_current_collection_datas(
                     creator_address.eq(excluded(creator_address)),
                     collection_name.eq(excluded(collection_name)),
                     description.eq(excluded(description)),
+                    names_encoded.eq(excluded(names_encoded)),
                     metadata_uri.eq(excluded(metadata_uri)),
                     supply.eq(excluded(supply)),
                     maximum.eq(excluded(maximum)),
@@ -384,7 +461,11 @@ fn insert_token_activities(
 ) -> Result<(), diesel::result::Error> {
     use schema::token_activities::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), TokenActivity::field_count());
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        TokenActivity::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -407,6 +488,72 @@ fn insert_token_activities(
     }
     Ok(())
 }
+
+fn insert_token_properties(
+    conn: &mut PgConnection,
+    items_to_insert: &[TokenProperty],
+) -> Result<(), diesel::result::Error> {
+    use schema::token_properties::dsl::*;
+
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        TokenProperty::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
+
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::token_properties::table)
+                .values(&items_to_insert[start_ind..end_ind])
+                .on_conflict((
+                    token_data_id_hash,
+                    property_version,
+                    transaction_version,
+                    property_key,
+                ))
+                .do_update()
+                .set((
+                    property_type.eq(excluded(property_type)),
+                    property_value.eq(excluded(property_value)),
+                    inserted_at.eq(excluded(inserted_at)),
+                )),
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_token_data_properties(
+    conn: &mut PgConnection,
+    items_to_insert: &[TokenDataProperty],
+) -> Result<(), diesel::result::Error> {
+    use schema::token_data_properties::dsl::*;
+
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        TokenDataProperty::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
+
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::token_data_properties::table)
+                .values(&items_to_insert[start_ind..end_ind])
+                .on_conflict((token_data_id_hash, transaction_version, property_key))
+                .do_update()
+                .set((
+                    property_type.eq(excluded(property_type)),
+                    property_value.eq(excluded(property_value)),
+                    inserted_at.eq(excluded(inserted_at)),
+                )),
+            None,
+        )?;
+    }
+    Ok(())
+}
+
 fn insert_current_token_claims(
     conn: &mut PgConnection,
     items_to_insert: &[CurrentTokenPendingClaim],
@@ -416,6 +563,7 @@ fn insert_current_token_claims(
     let chunks = get_chunks(
         items_to_insert.len(),
         CurrentTokenPendingClaim::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
     );
 
     for (start_ind, end_ind) in chunks {
@@ -434,6 +582,7 @@ fn insert_current_token_claims(
                     name.eq(excluded(name)),
                     amount.eq(excluded(amount)),
                     table_handle.eq(excluded(table_handle)),
+                    names_encoded.eq(excluded(names_encoded)),
                     last_transaction_version.eq(excluded(last_transaction_version)),
                     inserted_at.eq(excluded(inserted_at)),
                 )),
@@ -449,7 +598,11 @@ fn insert_current_ans_lookups(
 ) -> Result<(), diesel::result::Error> {
     use schema::current_ans_lookup::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentAnsLookup::field_count());
+    let chunks = get_chunks(
+        items_to_insert.len(),
+        CurrentAnsLookup::field_count(),
+        MAX_DIESEL_PARAM_SIZE,
+    );
 
     for (start_ind, end_ind) in chunks {
         execute_with_better_error(
@@ -481,6 +634,7 @@ impl TransactionProcessor for TokenTransactionProcessor {
         transactions: Vec<Transaction>,
         start_version: u64,
         end_version: u64,
+        dry_run: bool,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
         let mut conn = self.get_conn();
 
@@ -494,6 +648,8 @@ impl TransactionProcessor for TokenTransactionProcessor {
         let mut all_token_datas = vec![];
         let mut all_collection_datas = vec![];
         let mut all_token_activities = vec![];
+        let mut all_token_properties = vec![];
+        let mut all_token_data_properties = vec![];
 
         // Hashmap key will be the PK of the table, we do not want to send duplicates writes to the db within a batch
         let mut all_current_token_ownerships: HashMap<
@@ -521,7 +677,23 @@ impl TransactionProcessor for TokenTransactionProcessor {
                 current_token_datas,
                 current_collection_datas,
                 current_token_claims,
-            ) = Token::from_transaction(&txn, &table_handle_to_owner, &mut conn);
+            ) = Token::from_transaction(&txn, &table_handle_to_owner, &mut conn).map_err(
+                |err| {
+                    TransactionProcessingError::from_processing_error(
+                        err,
+                        txn.version().unwrap(),
+                        start_version,
+                        end_version,
+                        self.name(),
+                    )
+                },
+            )?;
+            all_token_properties.extend(tokens.iter().flat_map(TokenProperty::from_token));
+            all_token_data_properties.extend(
+                token_datas
+                    .iter()
+                    .flat_map(TokenDataProperty::from_token_data),
+            );
             all_tokens.append(&mut tokens);
             all_token_ownerships.append(&mut token_ownerships);
             all_token_datas.append(&mut token_datas);
@@ -532,7 +704,10 @@ impl TransactionProcessor for TokenTransactionProcessor {
             all_current_collection_datas.extend(current_collection_datas);
 
             // Track token activities
-            let mut activities = TokenActivity::from_transaction(&txn);
+            let mut activities = TokenActivity::from_transaction(&txn).map_err(|err| {
+                let version = txn.version().unwrap();
+                TransactionProcessingError::ParseError((err, version, version, self.name()))
+            })?;
             all_token_activities.append(&mut activities);
 
             // claims
@@ -590,33 +765,93 @@ impl TransactionProcessor for TokenTransactionProcessor {
         all_current_ans_lookups
             .sort_by(|a, b| a.domain.cmp(&b.domain).then(a.subdomain.cmp(&b.subdomain)));
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
+        let entity_counts = HashMap::from([
+            ("tokens", all_tokens.len()),
+            ("token_ownerships", all_token_ownerships.len()),
+            ("token_datas", all_token_datas.len()),
+            ("collection_datas", all_collection_datas.len()),
             (
-                all_tokens,
-                all_token_ownerships,
-                all_token_datas,
-                all_collection_datas,
+                "current_token_ownerships",
+                all_current_token_ownerships.len(),
             ),
+            ("current_token_datas", all_current_token_datas.len()),
             (
-                all_current_token_ownerships,
-                all_current_token_datas,
-                all_current_collection_datas,
+                "current_collection_datas",
+                all_current_collection_datas.len(),
             ),
-            all_token_activities,
-            all_current_token_claims,
-            all_current_ans_lookups,
-        );
+            ("token_activities", all_token_activities.len()),
+            ("token_properties", all_token_properties.len()),
+            ("token_data_properties", all_token_data_properties.len()),
+            (
+                "current_token_pending_claims",
+                all_current_token_claims.len(),
+            ),
+            ("current_ans_lookup", all_current_ans_lookups.len()),
+        ]);
+
+        let tx_result = if dry_run {
+            log_dry_run(self.name(), "tokens", &all_tokens);
+            log_dry_run(self.name(), "token_ownerships", &all_token_ownerships);
+            log_dry_run(self.name(), "token_datas", &all_token_datas);
+            log_dry_run(self.name(), "collection_datas", &all_collection_datas);
+            log_dry_run(
+                self.name(),
+                "current_token_ownerships",
+                &all_current_token_ownerships,
+            );
+            log_dry_run(self.name(), "current_token_datas", &all_current_token_datas);
+            log_dry_run(
+                self.name(),
+                "current_collection_datas",
+                &all_current_collection_datas,
+            );
+            log_dry_run(self.name(), "token_activities", &all_token_activities);
+            log_dry_run(self.name(), "token_properties", &all_token_properties);
+            log_dry_run(
+                self.name(),
+                "token_data_properties",
+                &all_token_data_properties,
+            );
+            log_dry_run(
+                self.name(),
+                "current_token_pending_claims",
+                &all_current_token_claims,
+            );
+            log_dry_run(self.name(), "current_ans_lookup", &all_current_ans_lookups);
+            Ok(())
+        } else {
+            insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                (
+                    all_tokens,
+                    all_token_ownerships,
+                    all_token_datas,
+                    all_collection_datas,
+                ),
+                (
+                    all_current_token_ownerships,
+                    all_current_token_datas,
+                    all_current_collection_datas,
+                ),
+                all_token_activities,
+                all_token_properties,
+                all_token_data_properties,
+                all_current_token_claims,
+                all_current_ans_lookups,
+            )
+            .await
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
                 start_version,
                 end_version,
+                entity_counts,
             )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+            Err(err) => Err(TransactionProcessingError::DatabaseError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
@@ -2,9 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    database::new_db_pool,
+    counters::{PROCESSOR_DATABASE_ERROR_RETRIES, PROCESSOR_DATA_FRESHNESS},
+    database::{new_db_pool, report_pool_metrics, PgDbPool},
     indexer::{
-        fetcher::TransactionFetcherOptions, tailer::Tailer,
+        errors::TransactionProcessingError,
+        fetcher::{backoff_with_jitter, TransactionFetcherOptions},
+        processing_result::ProcessingResult,
+        rest_fetcher::RestTransactionFetcher,
+        tailer::{await_tasks, check_for_gaps, BatchOutcome, Tailer},
         transaction_processor::TransactionProcessor,
     },
     processors::{
@@ -15,14 +20,24 @@ use crate::{
 };
 
 use aptos_api::context::Context;
-use aptos_config::config::{IndexerConfig, NodeConfig};
-use aptos_logger::{error, info};
+use aptos_api_types::Transaction;
+use aptos_config::config::{IndexerConfig, NodeConfig, ValidatedIndexerConfig};
+use aptos_logger::{error, info, warn};
 use aptos_mempool::MempoolClientSender;
 use aptos_types::chain_id::ChainId;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::Mutex,
+};
+
+/// How many times a batch that failed with a `DatabaseError` is retried before the processor gives up
+const DATABASE_ERROR_MAX_RETRIES: u32 = 5;
+const DATABASE_ERROR_RETRY_BASE: Duration = Duration::from_secs(1);
+const DATABASE_ERROR_RETRY_MAX: Duration = Duration::from_secs(60);
 
 pub struct MovingAverage {
     window_millis: u64,
@@ -52,7 +67,8 @@ impl MovingAverage {
             match self.values.front() {
                 None => break,
                 Some((ts, val)) => {
-                    if timestamp_millis - ts > self.window_millis {
+                    // `tick_now` reads the wall clock, which can step backwards
+                    if timestamp_millis.saturating_sub(*ts) > self.window_millis {
                         self.sum -= val;
                         self.values.pop_front();
                     } else {
@@ -68,7 +84,15 @@ impl MovingAverage {
         if self.values.len() < 2 {
             0.0
         } else {
-            let elapsed = self.values.back().unwrap().0 - self.values.front().unwrap().0;
+            let elapsed = self
+                .values
+                .back()
+                .unwrap()
+                .0
+                .saturating_sub(self.values.front().unwrap().0);
+            if elapsed == 0 {
+                return 0.0;
+            }
             self.sum as f64 / elapsed as f64
         }
     }
@@ -86,47 +110,121 @@ pub fn bootstrap(
         return None;
     }
 
-    let runtime = Builder::new_multi_thread()
-        .thread_name("indexer")
-        .disable_lifo_slot()
-        .enable_all()
-        .build()
-        .expect("[indexer] failed to create runtime");
+    // Fail here with a clear message rather than on an unwrap deep inside the indexer
+    let indexer_config = match config.indexer.validate_and_fill_defaults() {
+        Ok(indexer_config) => indexer_config,
+        Err(e) => return Some(Err(e)),
+    };
+
+    let runtime = new_runtime();
 
-    let indexer_config = config.indexer.clone();
     let node_config = config.clone();
 
     runtime.spawn(async move {
         let context = Arc::new(Context::new(chain_id, db, mp_sender, node_config));
-        run_forever(indexer_config, context).await;
+        run_forever(indexer_config, Some(context)).await;
     });
 
     Some(Ok(runtime))
 }
 
-pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
-    // All of these options should be filled already with defaults
-    let processor_name = config.processor.clone().unwrap();
-    let check_chain_id = config.check_chain_id.unwrap();
-    let skip_migrations = config.skip_migrations.unwrap();
-    let fetch_tasks = config.fetch_tasks.unwrap();
-    let processor_tasks = config.processor_tasks.unwrap();
-    let emit_every = config.emit_every.unwrap();
-    let batch_size = config.batch_size.unwrap();
-    let lookback_versions = config.gap_lookback_versions.unwrap() as i64;
+/// Like `bootstrap`, but for an indexer running on its own instead of inside a node. There's no local storage
+/// to read, so transactions come from `rest_fetcher_url`, which has to be set.
+pub fn bootstrap_standalone(config: &IndexerConfig) -> Option<anyhow::Result<Runtime>> {
+    if !config.enabled {
+        return None;
+    }
+
+    let indexer_config = match config.validate_and_fill_defaults() {
+        Ok(indexer_config) => indexer_config,
+        Err(e) => return Some(Err(e)),
+    };
+    if indexer_config.rest_fetcher_url.is_none() {
+        return Some(Err(anyhow::anyhow!(
+            "rest_fetcher_url must be set to run the indexer outside a node"
+        )));
+    }
+
+    let runtime = new_runtime();
+    runtime.spawn(run_forever(indexer_config, None));
 
-    info!(processor_name = processor_name, "Starting indexer...");
+    Some(Ok(runtime))
+}
+
+fn new_runtime() -> Runtime {
+    Builder::new_multi_thread()
+        .thread_name("indexer")
+        .disable_lifo_slot()
+        .enable_all()
+        .build()
+        .expect("[indexer] failed to create runtime")
+}
+
+/// `context` is the local node's. It can only be left out when transactions come from `rest_fetcher_url`.
+pub async fn run_forever(config: ValidatedIndexerConfig, context: Option<Arc<Context>>) {
+    // `processor` may list several processors separated by commas. Each one gets its own tailer,
+    // status and start version, while all of them share the connection pool.
+    let processor_names = config
+        .processor
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<String>>();
+
+    info!(config = ?config, "Indexer starting with configuration");
+    info!(processor_names = ?processor_names, "Starting indexer...");
 
-    let db_uri = &config.postgres_uri.unwrap();
     info!(
-        processor_name = processor_name,
+        processor_names = ?processor_names,
         "Creating connection pool..."
     );
-    let conn_pool = new_db_pool(db_uri).expect("Failed to create connection pool");
+    // `db_pool_size` is per processor
+    let conn_pool = new_db_pool(
+        &config.postgres_uri,
+        config.db_pool_size * processor_names.len() as u32,
+        config.db_pool_min_idle,
+        config.db_connection_timeout_secs,
+        // No single statement should outlast the batch it's part of
+        Some(Duration::from_secs(config.batch_timeout_secs)),
+    )
+    .expect("Failed to create connection pool");
     info!(
-        processor_name = processor_name,
+        processor_names = ?processor_names,
         "Created the connection pool... "
     );
+    tokio::spawn(report_pool_metrics(conn_pool.clone()));
+
+    let tasks = processor_names
+        .into_iter()
+        .map(|processor_name| {
+            tokio::spawn(run_processor(
+                config.clone(),
+                processor_name,
+                context.clone(),
+                conn_pool.clone(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    await_tasks(tasks).await;
+}
+
+async fn run_processor(
+    config: ValidatedIndexerConfig,
+    processor_name: String,
+    context: Option<Arc<Context>>,
+    conn_pool: PgDbPool,
+) {
+    let check_chain_id = config.check_chain_id;
+    let skip_migrations = config.skip_migrations;
+    let fetch_tasks = config.fetch_tasks;
+    let processor_tasks = config.processor_tasks;
+    let emit_every = config.emit_every;
+    let batch_size = config.batch_size;
+    let lookback_versions = config.gap_lookback_versions as i64;
+    let batch_timeout = Duration::from_secs(config.batch_timeout_secs);
+    let batch_timeout_max_retries = config.batch_timeout_max_retries;
+    let ending_version = config.ending_version;
+    let dry_run = config.dry_run;
 
     info!(processor_name = processor_name, "Instantiating tailer... ");
 
@@ -141,35 +239,83 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
         )),
         Processor::CoinProcessor => Arc::new(CoinTransactionProcessor::new(conn_pool.clone())),
         Processor::StakeProcessor => Arc::new(StakeTransactionProcessor::new(conn_pool.clone())),
+        Processor::MarketplaceProcessor => {
+            unreachable!("IndexerConfig validation rejects {}", processor_name)
+        }
     };
 
-    let options =
-        TransactionFetcherOptions::new(None, None, Some(batch_size), None, fetch_tasks as usize);
+    let mut options_builder = TransactionFetcherOptions::builder()
+        .batch_size(batch_size)
+        .fetch_tasks(fetch_tasks as usize);
+    if let Some(fetch_ahead_batches) = config.fetch_ahead_batches {
+        options_builder = options_builder.max_pending_batches(fetch_ahead_batches);
+    }
+    if let Some(fetch_retry_base_millis) = config.fetch_retry_base_millis {
+        options_builder = options_builder.starting_retry_time_millis(fetch_retry_base_millis);
+    }
+    if let Some(fetch_retry_max_millis) = config.fetch_retry_max_millis {
+        options_builder = options_builder.max_retry_time_millis(fetch_retry_max_millis);
+    }
+    let options = options_builder
+        .build()
+        .expect("Invalid transaction fetcher options");
 
-    let tailer = Tailer::new(context, conn_pool.clone(), processor, options)
-        .expect("Failed to instantiate tailer");
+    let mut tailer = match (&config.rest_fetcher_url, context) {
+        (Some(rest_fetcher_url), _) => {
+            info!(
+                processor_name = processor_name,
+                rest_fetcher_url = rest_fetcher_url,
+                "Fetching transactions from REST API"
+            );
+            let fetcher = RestTransactionFetcher::new(rest_fetcher_url, options.clone())
+                .expect("Invalid REST fetcher url");
+            Tailer::with_fetcher(
+                Arc::new(Mutex::new(fetcher)),
+                conn_pool.clone(),
+                processor,
+                options,
+            )
+        }
+        (None, Some(context)) => Tailer::new(context, conn_pool.clone(), processor, options)
+            .expect("Failed to instantiate tailer"),
+        (None, None) => panic!("[Indexer] rest_fetcher_url must be set to run outside a node"),
+    };
+    if dry_run {
+        info!(
+            processor_name = processor_name,
+            "Running in dry run mode, nothing will be written to the db"
+        );
+        tailer.dry_run = true;
+    }
+    tailer.max_writeset_changes_per_txn = config.max_writeset_changes_per_txn;
 
     if !skip_migrations {
         info!(processor_name = processor_name, "Running migrations...");
         tailer.run_migrations();
     }
 
+    if let Some(reset_to_version) = config.reset_to_version {
+        tailer
+            .reset_processor_version(&processor_name, reset_to_version, config.force_reset)
+            .unwrap_or_else(|e| panic!("Failed to reset processor version: {:?}", e));
+    }
+
     info!(
         processor_name = processor_name,
         lookback_versions = lookback_versions,
         "Fetching starting version from db..."
     );
-    // For now this is not being used but we'd want to track it anyway
-    let starting_version_from_db_short = tailer
+    // For now this is only used to detect gaps but we'd want to track it anyway
+    let maybe_starting_version_from_db = tailer
         .get_start_version(&processor_name)
-        .unwrap_or_else(|e| panic!("Failed to get starting version: {:?}", e))
-        .unwrap_or_else(|| {
-            info!(
-                processor_name = processor_name,
-                "No starting version from db so starting from version 0"
-            );
-            0
-        }) as u64;
+        .unwrap_or_else(|e| panic!("Failed to get starting version: {:?}", e));
+    let starting_version_from_db_short = maybe_starting_version_from_db.unwrap_or_else(|| {
+        info!(
+            processor_name = processor_name,
+            "No starting version from db so starting from version 0"
+        );
+        0
+    }) as u64;
     let start_version = match config.starting_version {
         None => tailer
             .get_start_version_long(&processor_name, lookback_versions)
@@ -182,6 +328,14 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
             }) as u64,
         Some(version) => version,
     };
+    // Only the local node's storage can have pruned the start version away
+    let start_version = if config.rest_fetcher_url.is_none() {
+        tailer
+            .clamp_start_version(&processor_name, start_version)
+            .unwrap_or_else(|e| panic!("Failed to check start version against the node: {:?}", e))
+    } else {
+        start_version
+    };
 
     info!(
         processor_name = processor_name,
@@ -190,6 +344,18 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
         starting_version_from_db_short = starting_version_from_db_short,
         "Setting starting version..."
     );
+    // A dry run doesn't write anything, gaps included
+    if let Some(expected_start_version) = maybe_starting_version_from_db.filter(|_| !dry_run) {
+        check_for_gaps(
+            &mut conn_pool
+                .get()
+                .expect("DB connection should be available to check for gaps"),
+            &processor_name,
+            expected_start_version as u64,
+            start_version,
+        )
+        .unwrap_or_else(|e| panic!("Failed to check for version gaps: {:?}", e));
+    }
     tailer.set_fetcher_version(start_version as u64).await;
 
     info!(processor_name = processor_name, "Starting fetcher...");
@@ -198,6 +364,7 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
     info!(
         processor_name = processor_name,
         start_version = start_version,
+        ending_version = ending_version,
         "Indexing loop started!"
     );
 
@@ -218,56 +385,64 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
         let other_tx = tx.clone();
         let other_tailer = tailer.clone();
         let task = tokio::task::spawn(async move {
-            loop {
-                let (num_res, res) = other_tailer.process_next_batch().await;
-                other_tx.send((num_res, res)).await.unwrap();
+            // Only stops once the batches have gone past `ending_version`
+            while let Some(transactions) = other_tailer.fetch_next_batch(ending_version).await {
+                let outcome = process_batch_skipping_unparseable(
+                    &other_tailer,
+                    transactions,
+                    batch_timeout,
+                    batch_timeout_max_retries,
+                )
+                .await;
+                other_tx.send(outcome).await.unwrap();
             }
         });
         tasks.push(task);
     }
+    // Once every task has stopped, the channel closes and we know all in-flight batches have been handled
+    drop(tx);
 
     let mut ma = MovingAverage::new(10_000);
+    // Batches can finish out of order, so freshness is measured against the newest timestamp seen so far
+    let mut latest_txn_timestamp: Option<chrono::NaiveDateTime> = None;
+
+    while let Some(outcome) = receiver.recv().await {
+        let num_res = outcome.num_txns;
+        let retry_count = outcome.retry_count;
+        let elapsed_ms = outcome.elapsed_ms;
+        let processing_result = processing_result_or_panic(&processor_name, outcome);
+
+        if !dry_run {
+            tailer
+                .update_last_processed_version(&processor_name, processing_result.end_version)
+                .unwrap_or_else(|e| {
+                    error!(
+                        processor_name = processor_name,
+                        start_version = processing_result.start_version,
+                        end_version = processing_result.end_version,
+                        retry_count = retry_count,
+                        error_type = "database",
+                        elapsed_ms = elapsed_ms,
+                        error = format!("{:?}", e),
+                        "Failed to update last processed version!"
+                    );
+                    panic!("Failed to update last processed version: {:?}", e);
+                });
+        }
 
-    loop {
-        let (num_res, result) = receiver
-            .recv()
-            .await
-            .expect("Failed to receive batch results: got None!");
-
-        let processing_result = match result {
-            Ok(res) => res,
-            Err(tpe) => {
-                let (err, start_version, end_version, _) = tpe.inner();
-                error!(
-                    processor_name = processor_name,
-                    start_version = start_version,
-                    end_version = end_version,
-                    error =? err,
-                    "Error processing batch!"
-                );
-                panic!(
-                    "Error in '{}' while processing batch: {:?}",
-                    processor_name, err
-                );
-            }
-        };
-
-        tailer
-            .update_last_processed_version(&processor_name, processing_result.end_version)
-            .unwrap_or_else(|e| {
-                error!(
-                    processor_name = processor_name,
-                    end_version = processing_result.end_version,
-                    error = format!("{:?}", e),
-                    "Failed to update last processed version!"
-                );
-                panic!("Failed to update last processed version: {:?}", e);
-            });
-
-        ma.tick_now(num_res);
+        latest_txn_timestamp = latest_txn_timestamp.max(processing_result.max_timestamp);
+        let chain_lag_secs =
+            latest_txn_timestamp.map(|ts| (chrono::Utc::now().naive_utc() - ts).num_seconds());
+        if let Some(chain_lag_secs) = chain_lag_secs {
+            PROCESSOR_DATA_FRESHNESS
+                .with_label_values(&[&processor_name])
+                .set(chain_lag_secs);
+        }
 
+        // Still counted when logging is off since the final summary uses it
         versions_processed += num_res;
         if emit_every != 0 {
+            ma.tick_now(num_res);
             let new_base: u64 = versions_processed / (emit_every as u64);
             if base != new_base {
                 base = new_base;
@@ -275,6 +450,10 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
                     processor_name = processor_name,
                     batch_start_version = processing_result.start_version,
                     batch_end_version = processing_result.end_version,
+                    batch_min_timestamp = ?processing_result.min_timestamp,
+                    batch_max_timestamp = ?processing_result.max_timestamp,
+                    chain_lag_secs = chain_lag_secs,
+                    entity_counts = ?processing_result.entity_counts,
                     versions_processed = versions_processed,
                     tps = (ma.avg() * 1000.0) as u64,
                     "Processed batch version"
@@ -282,4 +461,330 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
             }
         }
     }
+
+    info!(
+        processor_name = processor_name,
+        start_version = start_version,
+        ending_version = ending_version,
+        versions_processed = versions_processed,
+        "Reached ending version, indexer stopped"
+    );
+}
+
+/// Processes a batch, retrying it with backoff while it fails with a `DatabaseError`. Those are usually the db
+/// restarting or failing over, and processors only upsert, so running the whole batch again is safe.
+async fn process_batch_with_retries(
+    tailer: &Tailer,
+    transactions: Vec<Transaction>,
+    batch_timeout: Duration,
+    batch_timeout_max_retries: u64,
+) -> BatchOutcome {
+    let mut attempt = 0;
+    loop {
+        let outcome = tailer
+            .process_batch(
+                transactions.clone(),
+                batch_timeout,
+                batch_timeout_max_retries,
+            )
+            .await;
+        match &outcome.result {
+            Err(TransactionProcessingError::DatabaseError((
+                err,
+                start_version,
+                end_version,
+                processor_name,
+            ))) if attempt < DATABASE_ERROR_MAX_RETRIES => {
+                attempt += 1;
+                let delay = backoff_with_jitter(
+                    attempt,
+                    DATABASE_ERROR_RETRY_BASE,
+                    DATABASE_ERROR_RETRY_MAX,
+                );
+                PROCESSOR_DATABASE_ERROR_RETRIES
+                    .with_label_values(&[*processor_name])
+                    .inc();
+                warn!(
+                    processor_name = processor_name,
+                    start_version = start_version,
+                    end_version = end_version,
+                    attempt = attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    error = ?err,
+                    "Database error processing batch, retrying"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            _ => return outcome,
+        }
+    }
+}
+
+/// Processes a batch, skipping only the transactions that fail with a `ParseError`. Those are recorded as a gap
+/// and the versions on either side of them are processed as batches of their own, which keeps every other
+/// version's status accurate. The result covers the whole batch, with entity counts summed over what was processed.
+async fn process_batch_skipping_unparseable(
+    tailer: &Tailer,
+    transactions: Vec<Transaction>,
+    batch_timeout: Duration,
+    batch_timeout_max_retries: u64,
+) -> BatchOutcome {
+    let num_txns = transactions.len() as u64;
+    let batch_start_version = transactions.first().unwrap().version().unwrap();
+    let batch_end_version = transactions.last().unwrap().version().unwrap();
+    let mut batch_result = ProcessingResult::new(
+        tailer.processor_name(),
+        batch_start_version,
+        batch_end_version,
+        HashMap::new(),
+    );
+    let mut retry_count = 0;
+    let mut elapsed_ms = 0;
+
+    let mut pending = VecDeque::from([transactions]);
+    while let Some(transactions) = pending.pop_front() {
+        let outcome = process_batch_with_retries(
+            tailer,
+            transactions.clone(),
+            batch_timeout,
+            batch_timeout_max_retries,
+        )
+        .await;
+        retry_count += outcome.retry_count;
+        elapsed_ms += outcome.elapsed_ms;
+        match outcome.result {
+            Ok(res) => {
+                for (table_name, count) in res.entity_counts {
+                    *batch_result.entity_counts.entry(table_name).or_insert(0) += count;
+                }
+                batch_result.min_timestamp = [batch_result.min_timestamp, res.min_timestamp]
+                    .into_iter()
+                    .flatten()
+                    .min();
+                batch_result.max_timestamp = batch_result.max_timestamp.max(res.max_timestamp);
+            }
+            Err(TransactionProcessingError::ParseError((err, start_version, end_version, _))) => {
+                let versions = transactions
+                    .iter()
+                    .map(|txn| txn.version().unwrap())
+                    .collect::<Vec<u64>>();
+                // An error that doesn't point into this batch can't be narrowed down, so all of it is skipped
+                let (start_version, end_version) = if versions
+                    .iter()
+                    .any(|version| (start_version..=end_version).contains(version))
+                {
+                    (start_version, end_version)
+                } else {
+                    (versions[0], versions[versions.len() - 1])
+                };
+                let (before, rest): (Vec<_>, Vec<_>) = transactions
+                    .into_iter()
+                    .partition(|txn| txn.version().unwrap() < start_version);
+                let after = rest
+                    .into_iter()
+                    .filter(|txn| txn.version().unwrap() > end_version)
+                    .collect::<Vec<_>>();
+                // `process_transactions_with_status` has already marked these versions as failed in processor_statuses
+                error!(
+                    processor_name = tailer.processor_name(),
+                    start_version = start_version,
+                    end_version = end_version,
+                    batch_start_version = batch_start_version,
+                    batch_end_version = batch_end_version,
+                    error_type = "parse",
+                    error =? err,
+                    "Could not parse transactions, skipping them"
+                );
+                tailer
+                    .record_skipped_versions(start_version, end_version)
+                    .unwrap_or_else(|e| panic!("Failed to record skipped versions: {:?}", e));
+                for transactions in [after, before] {
+                    if !transactions.is_empty() {
+                        pending.push_front(transactions);
+                    }
+                }
+            }
+            Err(tpe) => {
+                return BatchOutcome {
+                    num_txns,
+                    retry_count,
+                    elapsed_ms,
+                    result: Err(tpe),
+                }
+            }
+        }
+    }
+    BatchOutcome {
+        num_txns,
+        retry_count,
+        elapsed_ms,
+        result: Ok(batch_result),
+    }
+}
+
+/// Returns the batch's result. Any error has already been retried or skipped as far as it's going to be,
+/// so it stops the processor.
+fn processing_result_or_panic(processor_name: &str, outcome: BatchOutcome) -> ProcessingResult {
+    let BatchOutcome {
+        retry_count,
+        elapsed_ms,
+        result,
+        ..
+    } = outcome;
+    match result {
+        Ok(res) => res,
+        Err(tpe) => {
+            let (err, start_version, end_version, _) = tpe.inner();
+            error!(
+                processor_name = processor_name,
+                start_version = start_version,
+                end_version = end_version,
+                retry_count = retry_count,
+                error_type = tpe.error_type(),
+                elapsed_ms = elapsed_ms,
+                error =? err,
+                "Error processing batch!"
+            );
+            panic!(
+                "Error in '{}' while processing batch: {:?}",
+                processor_name, err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        load_fixture_transactions, models::processor_version_gaps::REASON_PARSE_ERROR,
+        processors::stake_processor, schema::processor_statuses, schema::processor_version_gaps,
+        setup_test_database,
+    };
+    use aptos_api_test_context::new_test_context;
+    use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_only_unparseable_transactions_are_skipped() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, _db_lock) = setup_test_database();
+        let context = Arc::new(new_test_context("doesnt_matter".to_string(), true).context);
+        let tailer = Tailer::new(
+            context,
+            conn_pool.clone(),
+            Arc::new(StakeTransactionProcessor::new(conn_pool.clone())),
+            TransactionFetcherOptions::default(),
+        )
+        .unwrap();
+
+        // The StakePool resource in this transaction is missing `delegated_voter`. It's surrounded by
+        // copies of itself without a write set, which parse fine.
+        let bad_txn = load_fixture_transactions("stake_pool_missing_voter.json").remove(0);
+        let bad_version = bad_txn.version().unwrap();
+        let parseable_copy = |version: u64| {
+            let mut txn = bad_txn.clone();
+            if let Transaction::UserTransaction(user_txn) = &mut txn {
+                user_txn.info.version = version.into();
+                user_txn.info.changes.clear();
+            }
+            txn
+        };
+        let transactions = vec![
+            parseable_copy(bad_version - 1),
+            bad_txn.clone(),
+            parseable_copy(bad_version + 1),
+        ];
+
+        let outcome =
+            process_batch_skipping_unparseable(&tailer, transactions, Duration::from_secs(60), 0)
+                .await;
+        assert_eq!(outcome.num_txns, 3);
+        let res = processing_result_or_panic(stake_processor::NAME, outcome);
+        assert_eq!(res.start_version, bad_version - 1);
+        assert_eq!(res.end_version, bad_version + 1);
+
+        let mut conn = conn_pool.get().unwrap();
+        let statuses = processor_statuses::table
+            .filter(processor_statuses::name.eq(stake_processor::NAME))
+            .order(processor_statuses::version)
+            .select((processor_statuses::version, processor_statuses::success))
+            .load::<(i64, bool)>(&mut conn)
+            .unwrap();
+        let bad_version = bad_version as i64;
+        assert_eq!(
+            statuses,
+            vec![
+                (bad_version - 1, true),
+                (bad_version, false),
+                (bad_version + 1, true)
+            ]
+        );
+        let gaps = processor_version_gaps::table
+            .filter(processor_version_gaps::processor.eq(stake_processor::NAME))
+            .select((
+                processor_version_gaps::gap_start_version,
+                processor_version_gaps::gap_end_version,
+                processor_version_gaps::reason,
+            ))
+            .load::<(i64, i64, String)>(&mut conn)
+            .unwrap();
+        assert_eq!(
+            gaps,
+            vec![(bad_version, bad_version, REASON_PARSE_ERROR.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_moving_average_needs_two_values() {
+        let mut ma = MovingAverage::new(1000);
+        assert_eq!(ma.avg(), 0.0);
+        assert_eq!(ma.tick(100, 50), 0.0);
+        assert_eq!(ma.avg(), 0.0);
+    }
+
+    #[test]
+    fn test_moving_average_evicts_old_values() {
+        let mut ma = MovingAverage::new(1000);
+        ma.tick(0, 10);
+        ma.tick(500, 10);
+        ma.tick(1000, 10);
+        assert_eq!(ma.values.len(), 3);
+        assert_eq!(ma.sum, 30);
+
+        // 0 is now more than a window old
+        ma.tick(1001, 10);
+        assert_eq!(ma.values.len(), 3);
+        assert_eq!(ma.sum, 30);
+
+        // Evicts several at once and keeps the sum in step
+        ma.tick(3000, 5);
+        assert_eq!(ma.values.len(), 1);
+        assert_eq!(ma.sum, 5);
+        ma.tick(3500, 5);
+        assert_eq!(ma.sum, 10);
+        assert_eq!(ma.sum, ma.values.iter().map(|(_, v)| v).sum::<u64>());
+    }
+
+    #[test]
+    fn test_moving_average_handles_clock_going_backwards() {
+        let mut ma = MovingAverage::new(1000);
+        ma.tick(1000, 10);
+        ma.tick(1000, 10);
+        assert_eq!(ma.avg(), 0.0);
+        ma.tick(900, 10);
+        assert_eq!(ma.sum, 30);
+    }
+
+    #[test]
+    fn test_moving_average_stable_rate() {
+        // One version every 10ms is 100 per second
+        let mut ma = MovingAverage::new(10_000);
+        for i in 0..5_000 {
+            ma.tick(i * 10, 1);
+        }
+        let tps = ma.avg() * 1000.0;
+        assert!((tps - 100.0).abs() < 1.0, "tps was {}", tps);
+    }
 }
@@ -31,6 +31,7 @@ diesel::table! {
         inserted_at -> Timestamp,
         table_handle -> Varchar,
         transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
     }
 }
 
@@ -73,6 +74,7 @@ diesel::table! {
         inserted_at -> Timestamp,
         table_handle -> Varchar,
         last_transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
     }
 }
 
@@ -109,6 +111,7 @@ diesel::table! {
         collection_data_id_hash -> Varchar,
         last_transaction_timestamp -> Timestamp,
         description -> Text,
+        names_encoded -> Bool,
     }
 }
 
@@ -127,6 +130,7 @@ diesel::table! {
         collection_data_id_hash -> Varchar,
         table_type -> Text,
         last_transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
     }
 }
 
@@ -145,6 +149,7 @@ diesel::table! {
         last_transaction_version -> Int8,
         inserted_at -> Timestamp,
         last_transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
     }
 }
 
@@ -185,6 +190,10 @@ diesel::table! {
         price -> Int8,
         maker -> Varchar,
         timestamp -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        txn_version -> Int8,
+        names_encoded -> Bool,
     }
 }
 
@@ -193,11 +202,15 @@ diesel::table! {
         creator_address -> Varchar,
         collection_name -> Text,
         creation_timestamp -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        txn_version -> Int8,
+        names_encoded -> Bool,
     }
 }
 
 diesel::table! {
-    marketplace_offers (token_name, property_version, price, seller) {
+    marketplace_offers (creator_address, collection_name, token_name, property_version) {
         creator_address -> Varchar,
         collection_name -> Text,
         token_name -> Text,
@@ -205,6 +218,10 @@ diesel::table! {
         price -> Int8,
         seller -> Varchar,
         timestamp -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        txn_version -> Int8,
+        names_encoded -> Bool,
     }
 }
 
@@ -218,6 +235,10 @@ diesel::table! {
         quantity -> Int8,
         maker -> Varchar,
         timestamp -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        txn_version -> Int8,
+        names_encoded -> Bool,
     }
 }
 
@@ -262,6 +283,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    processor_version_gaps (processor, gap_start_version) {
+        processor -> Varchar,
+        gap_start_version -> Int8,
+        gap_end_version -> Int8,
+        inserted_at -> Timestamp,
+        reason -> Varchar,
+    }
+}
+
 diesel::table! {
     processor_statuses (name, version) {
         name -> Varchar,
@@ -333,6 +364,19 @@ diesel::table! {
         coin_amount -> Nullable<Numeric>,
         inserted_at -> Timestamp,
         transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
+    }
+}
+
+diesel::table! {
+    token_data_properties (token_data_id_hash, transaction_version, property_key) {
+        token_data_id_hash -> Varchar,
+        transaction_version -> Int8,
+        property_key -> Text,
+        property_type -> Text,
+        property_value -> Text,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
     }
 }
 
@@ -361,6 +405,7 @@ diesel::table! {
         collection_data_id_hash -> Varchar,
         transaction_timestamp -> Timestamp,
         description -> Text,
+        names_encoded -> Bool,
     }
 }
 
@@ -380,6 +425,20 @@ diesel::table! {
         inserted_at -> Timestamp,
         collection_data_id_hash -> Varchar,
         transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
+    }
+}
+
+diesel::table! {
+    token_properties (token_data_id_hash, property_version, transaction_version, property_key) {
+        token_data_id_hash -> Varchar,
+        property_version -> Numeric,
+        transaction_version -> Int8,
+        property_key -> Text,
+        property_type -> Text,
+        property_value -> Text,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
     }
 }
 
@@ -396,6 +455,7 @@ diesel::table! {
         inserted_at -> Timestamp,
         collection_data_id_hash -> Varchar,
         transaction_timestamp -> Timestamp,
+        names_encoded -> Bool,
     }
 }
 
@@ -476,12 +536,15 @@ diesel::allow_tables_to_appear_in_same_query!(
     move_resources,
     processor_status,
     processor_statuses,
+    processor_version_gaps,
     signatures,
     table_items,
     table_metadatas,
     token_activities,
+    token_data_properties,
     token_datas,
     token_ownerships,
+    token_properties,
     tokens,
     transactions,
     user_transactions,
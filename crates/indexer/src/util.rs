@@ -17,10 +17,75 @@ pub fn hash_str(val: &str) -> String {
     hex::encode(sha2::Sha256::digest(val.as_bytes()))
 }
 
+/// Cuts on a char boundary, since names can hold multi-byte characters and `String::truncate` panics mid-char
 pub fn truncate_str(val: &str, max_chars: usize) -> String {
-    let mut trunc = val.to_string();
-    trunc.truncate(max_chars);
-    trunc
+    val.chars().take(max_chars).collect()
+}
+
+/// Names come straight from chain and can hold control characters, which break JSON serialization
+/// and, for null bytes, postgres inserts. Names without any are only truncated, so they're stored as before.
+/// Otherwise control characters are replaced by their escaped form, e.g. `\u{0}`, and backslashes are doubled
+/// so `unescape_name` can give back the on-chain name. The result is at most `max_chars` long, cut before an
+/// escape sequence rather than through it. Also returns whether the name was escaped, which is stored next to
+/// it so readers know whether to unescape.
+pub fn sanitize_name_trunc(val: &str, max_chars: usize) -> (String, bool) {
+    let truncated = truncate_str(val, max_chars);
+    if !truncated.chars().any(char::is_control) {
+        return (truncated, false);
+    }
+    let mut sanitized = String::new();
+    let mut sanitized_chars = 0;
+    for c in val.chars() {
+        let escape = if c == '\\' {
+            Some("\\\\".to_string())
+        } else if c.is_control() {
+            Some(format!("\\u{{{:x}}}", c as u32))
+        } else {
+            None
+        };
+        let escape_chars = escape.as_ref().map_or(1, |escape| escape.chars().count());
+        if sanitized_chars + escape_chars > max_chars {
+            break;
+        }
+        sanitized_chars += escape_chars;
+        match escape {
+            Some(escape) => sanitized.push_str(&escape),
+            None => sanitized.push(c),
+        }
+    }
+    (sanitized, true)
+}
+
+/// `sanitize_name_trunc` without a length limit
+pub fn sanitize_name(val: &str) -> (String, bool) {
+    sanitize_name_trunc(val, usize::MAX)
+}
+
+/// Reverses `sanitize_name` for a name stored as escaped, e.g. to recompute the hash of a stored name
+pub fn unescape_name(val: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = val.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('u') => {
+                let code = chars
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|c| *c != '}')
+                    .collect::<String>();
+                if let Some(c) = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                    unescaped.push(c);
+                }
+            }
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
 }
 
 pub fn u64_to_bigdecimal(val: u64) -> BigDecimal {
@@ -52,32 +117,42 @@ pub fn parse_timestamp_secs(ts: u64, version: i64) -> chrono::NaiveDateTime {
     .unwrap_or_else(|| panic!("Could not parse timestamp {:?} for version {}", ts, version))
 }
 
-pub fn remove_null_bytes<T: serde::Serialize + for<'de> serde::Deserialize<'de>>(input: &T) -> T {
+/// Returns `input` with the null bytes removed from all of its strings, or `None` if it didn't have any
+pub fn remove_null_bytes<T: serde::Serialize + for<'de> serde::Deserialize<'de>>(
+    input: &T,
+) -> Option<T> {
     let mut txn_json = serde_json::to_value(input).unwrap();
-    recurse_remove_null_bytes_from_json(&mut txn_json);
-    serde_json::from_value::<T>(txn_json).unwrap()
+    if recurse_remove_null_bytes_from_json(&mut txn_json) {
+        Some(serde_json::from_value::<T>(txn_json).unwrap())
+    } else {
+        None
+    }
 }
 
-fn recurse_remove_null_bytes_from_json(sub_json: &mut Value) {
+/// Returns whether any string had to be changed
+fn recurse_remove_null_bytes_from_json(sub_json: &mut Value) -> bool {
+    let mut changed = false;
     match sub_json {
         Value::Array(array) => {
             for item in array {
-                recurse_remove_null_bytes_from_json(item);
+                changed |= recurse_remove_null_bytes_from_json(item);
             }
         }
         Value::Object(object) => {
             for (_key, value) in object {
-                recurse_remove_null_bytes_from_json(value);
+                changed |= recurse_remove_null_bytes_from_json(value);
             }
         }
         Value::String(str) => {
             if !str.is_empty() {
                 let replacement = string_null_byte_replacement(str);
+                changed = replacement != *str;
                 *str = replacement;
             }
         }
         _ => {}
     }
+    changed
 }
 
 fn string_null_byte_replacement(value: &mut str) -> String {
@@ -103,4 +178,49 @@ mod tests {
         let ts3 = parse_timestamp_secs(1659386386, 2);
         assert_eq!(ts3.timestamp(), 1659386386);
     }
+
+    #[test]
+    fn test_truncate_str_multi_byte() {
+        assert_eq!(truncate_str("ab", 5), "ab");
+        assert_eq!(truncate_str("🐒🐒🐒", 2), "🐒🐒");
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(
+            sanitize_name("Aptos Monkey #42"),
+            ("Aptos Monkey #42".to_string(), false)
+        );
+        assert_eq!(
+            sanitize_name("bad\u{0}name\n"),
+            ("bad\\u{0}name\\u{a}".to_string(), true)
+        );
+        // Names without control characters are stored as they are, backslashes included
+        assert_eq!(sanitize_name("name\\n"), ("name\\n".to_string(), false));
+        // Once a name is escaped, a real newline and a literal `\u{a}` must not be stored the same way
+        assert_eq!(
+            sanitize_name("\n\\u{a}"),
+            ("\\u{a}\\\\u{a}".to_string(), true)
+        );
+        for name in ["bad\u{0}name\n", "name\\u{0}\n", "🐒\u{7f}🐒"] {
+            assert_eq!(unescape_name(&sanitize_name(name).0), name);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_name_trunc() {
+        assert_eq!(
+            sanitize_name_trunc("Aptos Monkey #42", 5),
+            ("Aptos".to_string(), false)
+        );
+        // Never cuts through an escape sequence, and stays within the limit once escaped
+        assert_eq!(
+            sanitize_name_trunc("ab\u{0}cd", 6),
+            ("ab".to_string(), true)
+        );
+        assert_eq!(
+            sanitize_name_trunc("ab\u{0}cd", 8),
+            ("ab\\u{0}c".to_string(), true)
+        );
+    }
 }
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_config::config::{DEFAULT_DB_CONNECTION_TIMEOUT_SECS, DEFAULT_DB_POOL_SIZE};
 use aptos_indexer::{
     database::{new_db_pool, PgDbPool, PgPoolConnection},
     models::transactions::TransactionQuery,
@@ -27,7 +28,13 @@ pub fn get_database_url() -> String {
 }
 
 pub fn setup_indexer() -> anyhow::Result<PgDbPool> {
-    let conn_pool = new_db_pool(get_database_url().as_str())?;
+    let conn_pool = new_db_pool(
+        get_database_url().as_str(),
+        DEFAULT_DB_POOL_SIZE,
+        None,
+        DEFAULT_DB_CONNECTION_TIMEOUT_SECS,
+        None,
+    )?;
     wipe_database(&mut conn_pool.get()?);
     Ok(conn_pool)
 }